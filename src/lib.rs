@@ -24,7 +24,7 @@ doc = ::embed_doc_image::embed_image!("circle.svg", "docs/img/circle.svg"),
 #![deny(missing_docs)]
 
 use bounding_box::BoundingBox;
-use std::{ffi::OsStr, path::Path};
+use std::{ffi::OsStr, io::Write, path::Path};
 
 // Make cairo importable from this crate
 pub use cairo;
@@ -41,8 +41,212 @@ The [`Viewport::write_to_file`] struct provides a simplified interface by
 reading out the file type from the provided target file path and selecting the
 corresponding [cairo] implementation. If none of the listed file extensions can
 be recognized in the given path, an [`Error::UnknowFileExt`] is returned.
+
+If the `image` feature is enabled, this list additionally contains the raster
+formats which are encoded via the [image](https://crates.io/crates/image) crate
+(e.g. .jpg), see [`OutputFormat`].
+
+This list is derived from [`OutputFormat::ALL`] via [`OutputFormat::extensions`].
+ */
+pub const CAIRO_FILE_EXTENSIONS: &[&str] = &{
+    let mut extensions = [""; EXTENSION_COUNT];
+    let mut n = 0;
+    let mut i = 0;
+    while i < OutputFormat::ALL.len() {
+        let format_extensions = OutputFormat::ALL[i].extensions();
+        let mut j = 0;
+        while j < format_extensions.len() {
+            extensions[n] = format_extensions[j];
+            n += 1;
+            j += 1;
+        }
+        i += 1;
+    }
+    extensions
+};
+
+/// Number of entries in [`CAIRO_FILE_EXTENSIONS`].
+const EXTENSION_COUNT: usize = {
+    let mut n = 0;
+    let mut i = 0;
+    while i < OutputFormat::ALL.len() {
+        n += OutputFormat::ALL[i].extensions().len();
+        i += 1;
+    }
+    n
+};
+
+/**
+Output formats supported by [`Viewport`].
+
+The meaning of the image units ([`Viewport::width`], [`Viewport::height`]) is
+format-dependent, see [`SideLength`]. [`Viewport::write_to_file`] derives the
+format from the file extension via [`OutputFormat::from_path`], while
+[`Viewport::write_to_file_as`], [`Viewport::write_to`] and
+[`Viewport::render_to_vec`] take it explicitly.
+
+The raster formats .jpg, .webp, .tiff, .bmp and .gif are not supported by cairo
+itself. They are only available if the `image` feature is enabled and are
+encoded via the [image](https://crates.io/crates/image) crate after drawing
+onto an [`ImageSurface`](cairo::ImageSurface), see [`RasterOptions`].
  */
-pub const CAIRO_FILE_EXTENSIONS: &[&str] = &["pdf", "png", "ps", "svg"];
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum OutputFormat {
+    /// Portable Network Graphics (raster image).
+    Png,
+    /// Portable Document Format.
+    Pdf,
+    /// PostScript.
+    Ps,
+    /// Encapsulated PostScript (a single page PostScript file meant to be
+    /// embedded in other documents). The bounding box of the file is the
+    /// image size.
+    Eps,
+    /// Scalable Vector Graphics.
+    Svg,
+    /// JPEG (raster image without transparency).
+    #[cfg(feature = "image")]
+    Jpeg,
+    /// WebP (lossless raster image).
+    #[cfg(feature = "image")]
+    Webp,
+    /// Tagged Image File Format (raster image).
+    #[cfg(feature = "image")]
+    Tiff,
+    /// Windows bitmap (raster image).
+    #[cfg(feature = "image")]
+    Bmp,
+    /// Graphics Interchange Format (raster image with a palette of 256 colors).
+    #[cfg(feature = "image")]
+    Gif,
+}
+
+impl OutputFormat {
+    /// All output formats.
+    pub const ALL: &'static [OutputFormat] = &[
+        OutputFormat::Png,
+        OutputFormat::Pdf,
+        OutputFormat::Ps,
+        OutputFormat::Eps,
+        OutputFormat::Svg,
+        #[cfg(feature = "image")]
+        OutputFormat::Jpeg,
+        #[cfg(feature = "image")]
+        OutputFormat::Webp,
+        #[cfg(feature = "image")]
+        OutputFormat::Tiff,
+        #[cfg(feature = "image")]
+        OutputFormat::Bmp,
+        #[cfg(feature = "image")]
+        OutputFormat::Gif,
+    ];
+
+    /**
+    Returns the file extension (without the leading dot) of the format.
+
+    ```
+    use cairo_viewport::OutputFormat;
+
+    assert_eq!(OutputFormat::Svg.extension(), "svg");
+    ```
+     */
+    pub const fn extension(&self) -> &'static str {
+        return self.extensions()[0];
+    }
+
+    /**
+    Returns all file extensions (without the leading dot) which are recognized
+    for the format. The first one is the [`OutputFormat::extension`].
+
+    ```
+    use cairo_viewport::OutputFormat;
+
+    assert_eq!(OutputFormat::Pdf.extensions(), &["pdf"]);
+    ```
+     */
+    pub const fn extensions(&self) -> &'static [&'static str] {
+        return match self {
+            OutputFormat::Png => &["png"],
+            OutputFormat::Pdf => &["pdf"],
+            OutputFormat::Ps => &["ps"],
+            OutputFormat::Eps => &["eps"],
+            OutputFormat::Svg => &["svg"],
+            #[cfg(feature = "image")]
+            OutputFormat::Jpeg => &["jpg", "jpeg"],
+            #[cfg(feature = "image")]
+            OutputFormat::Webp => &["webp"],
+            #[cfg(feature = "image")]
+            OutputFormat::Tiff => &["tiff", "tif"],
+            #[cfg(feature = "image")]
+            OutputFormat::Bmp => &["bmp"],
+            #[cfg(feature = "image")]
+            OutputFormat::Gif => &["gif"],
+        };
+    }
+
+    /**
+    Returns the format which belongs to the given file extension (without the
+    leading dot). The comparison is case-insensitive.
+
+    ```
+    use cairo_viewport::OutputFormat;
+
+    assert_eq!(OutputFormat::from_extension("PNG"), Some(OutputFormat::Png));
+    assert_eq!(OutputFormat::from_extension("txt"), None);
+    ```
+     */
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        return Self::ALL.iter().copied().find(|format| {
+            format
+                .extensions()
+                .iter()
+                .any(|ext| ext.eq_ignore_ascii_case(extension))
+        });
+    }
+
+    /**
+    Derives the format from the file extension of `path`. The comparison is
+    case-insensitive.
+
+    Returns [`Error::UnknowFileExt`] if `path` has no file extension or if it
+    does not match any of [`CAIRO_FILE_EXTENSIONS`].
+
+    ```
+    use cairo_viewport::OutputFormat;
+
+    assert_eq!(OutputFormat::from_path("plot.PNG").unwrap(), OutputFormat::Png);
+    assert_eq!(OutputFormat::from_path("dir/plot.pdf").unwrap(), OutputFormat::Pdf);
+    assert!(OutputFormat::from_path("plot").is_err());
+    assert!(OutputFormat::from_path("plot.txt").is_err());
+    ```
+     */
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        match path.as_ref().extension().and_then(OsStr::to_str) {
+            Some(ext) => {
+                // Check if the provided file extension matches one of the available file
+                // extension types
+                match Self::from_extension(ext) {
+                    Some(format) => return Ok(format),
+                    None => {
+                        let msg = format!(
+                            "The given file extension \"{}\" is not recognized. Available file extensions are: {}",
+                            ext,
+                            CAIRO_FILE_EXTENSIONS.join(", ")
+                        );
+                        return Err(Error::UnknowFileExt(msg));
+                    }
+                }
+            }
+            None => {
+                let msg = format!(
+                    "No file extension has been recognized. Add one of the following file extensions: {}",
+                    CAIRO_FILE_EXTENSIONS.join(", ")
+                );
+                return Err(Error::UnknowFileExt(msg));
+            }
+        };
+    }
+}
 
 /**
 A viewport which can be used to scale and translate the origin of a [`cairo::Context`].
@@ -50,14 +254,14 @@ A viewport which can be used to scale and translate the origin of a [`cairo::Con
 This struct simplifies the process of configuring a [`cairo::Surface`] and its
 corresponding [`cairo::Context`] via the following workflow:
 1) Define the bounds of the drawing with the fields [`Viewport::origin`] and
-[`Viewport::origin`]. These values are used to scale and translate a
-[`cairo::Context`] to make sure the image produced by [cairo] actually shows
-the drawing
+   [`Viewport::scale`]. These values are used to scale and translate a
+   [`cairo::Context`] to make sure the image produced by [cairo] actually shows
+   the drawing
 2) Define the dimension of the image with the fields [`Viewport::width`] and
-[`Viewport::height`]. Depending on the file type used for the image, these
-values have different meanings:
+   [`Viewport::height`]. Depending on the file type used for the image, these
+   values have different meanings:
     - pdf: Points on the screen (1/72 inch on a screen at 100 % scale)
-    - png: Pixel
+    - png (and jpg, webp, tiff, bmp and gif): Pixel
     - ps: Points on the screen (1/72 inch on a screen at 100 % scale)
     - svg: CSS pixel on the screen (ca. 1/96 inch on a screen at 100 % scale)
 
@@ -77,7 +281,7 @@ let bb = BoundingBox::new(6.0, 8.0, 12.0, 20.0);
 let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(500));
 
 assert_eq!(viewport.origin, [-6.0, -12.0]); // Negated lower left corner of bb
-assert_eq!(viewport.scale, 62.5); // (500 * 2/8) / 2
+assert_eq!(viewport.scale, [62.5, 62.5]); // (500 * 2/8) / 2
 assert_eq!(viewport.width, 125);
 assert_eq!(viewport.height, 500);
 ```
@@ -89,52 +293,200 @@ pub struct Viewport {
      */
     pub origin: [f64; 2],
     /**
-    The context is scaled (both x- and y- dimension) by this value before
+    The context is scaled by these values (x- and y-dimension) before
     translation.
+
+    If both values differ, the drawing is distorted, which is useful e.g. if
+    the x- and y-coordinates of the drawing have different units. Be aware that
+    this also distorts strokes (which are thicker in one direction than in the
+    other). Text drawn via the cairo toy text API is not distorted if its size
+    is set via [`Viewport::set_font_size`].
      */
-    pub scale: f64,
+    pub scale: [f64; 2],
     /// Width of the surface.
     pub width: u32,
     /// Height of the surface.
     pub height: u32,
+    /**
+    Orientation of the y-axis of the drawing. See [`YAxis`] for details.
+     */
+    pub y_axis: YAxis,
+    /**
+    The drawing is rotated by this angle (in radians) around the origin of the
+    drawing coordinates before scaling and translation.
+
+    A positive angle rotates the positive x-axis towards the positive y-axis.
+    For [`YAxis::Down`], this is a clockwise rotation of the image, for
+    [`YAxis::Up`] a counterclockwise one.
+     */
+    pub rotation: f64,
+    /**
+    If set, a [`DebugOverlay`] is drawn on top of the output of the draw
+    callback, see [`Viewport::with_debug_overlay`].
+     */
+    pub debug_overlay: Option<DebugOverlay>,
 }
 
 impl Viewport {
     /**
     Creates a [`Viewport`] from its components. This is a wrapper around the
     direct construction of the struct from its fields and does not perform any
-    calculations or checks.
+    calculations or checks. The y-axis points downwards ([`YAxis::Down`]), the
+    drawing is not rotated and no [`DebugOverlay`] is drawn.
      */
-    pub fn new(origin: [f64; 2], scale: f64, width: u32, height: u32) -> Self {
+    pub fn new(origin: [f64; 2], scale: [f64; 2], width: u32, height: u32) -> Self {
         return Viewport {
             origin,
             scale,
             width,
             height,
+            y_axis: YAxis::Down,
+            rotation: 0.0,
+            debug_overlay: None,
         };
     }
 
+    /**
+    Returns `self` with the given [`YAxis`] orientation.
+
+    Since [`YAxis::Up`] flips the drawing around the horizontal center line of
+    the image, the region shown in the image is the same for both orientations.
+    Hence, a [`Viewport`] created via [`Viewport::from_bounding_box`] still
+    fits its bounding box after changing the orientation.
+
+    # Examples
+
+    ```
+    use cairo_viewport::{Viewport, SideLength, YAxis};
+    use bounding_box::BoundingBox;
+
+    let bb = BoundingBox::new(0.0, 2.0, 0.0, 1.0);
+    let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(200))
+        .with_y_axis(YAxis::Up);
+
+    let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, 200, 100).unwrap();
+    let cr = cairo::Context::new(&surface).unwrap();
+    viewport.apply(&cr);
+
+    // ymax is at the top of the image, ymin at the bottom
+    assert_eq!(cr.user_to_device(0.0, 1.0), (0.0, 0.0));
+    assert_eq!(cr.user_to_device(2.0, 0.0), (200.0, 100.0));
+    ```
+     */
+    pub fn with_y_axis(mut self, y_axis: YAxis) -> Self {
+        self.y_axis = y_axis;
+        return self;
+    }
+
+    /**
+    Returns `self` with the given [`DebugOverlay`], which is drawn on top of
+    the output of the draw callback of all drawing methods (e.g.
+    [`Viewport::write_to_file`], [`Viewport::with_context`] or
+    [`ViewportDocument::add_page`]). This shows the world frame of a drawing
+    without changing the drawing code.
+
+    # Examples
+
+    ```
+    use cairo_viewport::{DebugOverlay, Viewport, SideLength};
+    use bounding_box::BoundingBox;
+
+    let bb = BoundingBox::new(-1.0, 6.0, -3.0, 3.0);
+    let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(500))
+        .with_debug_overlay(DebugOverlay {
+            bounding_box: Some(bb),
+            ..Default::default()
+        });
+
+    let path = std::env::temp_dir().join("cairo_viewport_debug_overlay.png");
+    viewport.write_to_file(&path, |cr| {
+        cr.rectangle(0.0, -2.0, 5.0, 4.0);
+        return cr.fill();
+    }).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    ```
+     */
+    pub fn with_debug_overlay(mut self, overlay: DebugOverlay) -> Self {
+        self.debug_overlay = Some(overlay);
+        return self;
+    }
+
     /**
     Converts `entity` into a [`BoundingBox`] and then calls
     [`Viewport::from_bounding_box`]. See the docstring of this method for more.
+
+    # Panics
+    Panics if the bounding box is infinite or if both its width and height are
+    zero. Use [`Viewport::try_from_bounded_entity`] to get an error instead.
      */
     pub fn from_bounded_entity<B: Into<BoundingBox>>(entity: B, side_length: SideLength) -> Self {
         return Self::from_bounding_box(&entity.into(), side_length);
     }
 
+    /**
+    Converts `entity` into a [`BoundingBox`] and then calls
+    [`Viewport::try_from_bounding_box`]. See the docstring of this method for more.
+     */
+    pub fn try_from_bounded_entity<B: Into<BoundingBox>>(
+        entity: B,
+        side_length: SideLength,
+    ) -> Result<Self, Error> {
+        return Self::try_from_bounding_box(&entity.into(), side_length);
+    }
+
+    /**
+    Calculates the common [`BoundingBox`] of all entities and then forwards it
+    to [`Viewport::try_from_bounding_box`]. See the docstring of this method for
+    more.
+
+    Returns [`Error::NoEntities`] if `entities` is empty. This function is
+    identical to [`Viewport::try_from_bounded_entities`] and therefore also
+    returns an error instead of panicking for an invalid common bounding box.
+     */
+    pub fn from_bounded_entities<B: Into<BoundingBox>>(
+        entities: impl Iterator<Item = B>,
+        side_length: SideLength,
+    ) -> Result<Self, Error> {
+        return Self::try_from_bounded_entities(entities, side_length);
+    }
+
     /**
     Calculates the common [`BoundingBox`] of all entities and then forwards it
-    to [`Viewport::from_bounding_box`]. See the docstring of this method for more.
+    to [`Viewport::try_from_bounding_box`]. See the docstring of this method for
+    more.
+
+    Returns [`Error::NoEntities`] if `entities` is empty.
+
+    # Examples
+
+    ```
+    use cairo_viewport::{Error, Viewport, SideLength};
+    use bounding_box::BoundingBox;
+
+    let entities = vec![
+        BoundingBox::new(0.0, 1.0, 0.0, 1.0),
+        BoundingBox::new(2.0, 3.0, 0.0, 1.0),
+    ];
+    let viewport = Viewport::try_from_bounded_entities(entities.into_iter(), SideLength::Long(300))
+        .expect("bounding box is valid");
+    assert_eq!(viewport.width, 300);
+    assert_eq!(viewport.height, 100);
+
+    let entities: Vec<BoundingBox> = Vec::new();
+    assert!(matches!(
+        Viewport::try_from_bounded_entities(entities.into_iter(), SideLength::Long(300)),
+        Err(Error::NoEntities)
+    ));
+    ```
      */
-    pub fn from_bounded_entities<B: Into<BoundingBox> + ?Sized>(
+    pub fn try_from_bounded_entities<B: Into<BoundingBox>>(
         entities: impl Iterator<Item = B>,
         side_length: SideLength,
-    ) -> Result<Self, &'static str> {
-        return Ok(Self::from_bounding_box(
-            &BoundingBox::from_bounded_entities(entities)
-                .ok_or("entities iterator must yield at least one item")?,
+    ) -> Result<Self, Error> {
+        return Self::try_from_bounding_box(
+            &BoundingBox::from_bounded_entities(entities).ok_or(Error::NoEntities)?,
             side_length,
-        ));
+        );
     }
 
     /**
@@ -143,16 +495,24 @@ impl Viewport {
 
     This function first calculates the `width` and `height` fields from
     [`SideLength::to_width_and_height`] using the given [`BoundingBox`].
-    With these values, the `scale` factor can then be calculated. The origin
-    is simply `[-bounding_box.xmin(), -bounding_box.ymin()]`.
+    With these values, the `scale` factor (identical for both dimensions) can
+    then be calculated. The origin is simply
+    `[-bounding_box.xmin(), -bounding_box.ymin()]`.
 
     Constructing a [`Viewport`] in this manner ensures that any image created
     from [`Viewport`] fits the given [`BoundingBox`]. This can be used to make
     sure that a drawing (whose [`BoundingBox`] is known) exactly fills the
-    image.
+    image. The returned [`Viewport`] uses [`YAxis::Down`], which can be changed
+    via [`Viewport::with_y_axis`] without affecting the fit.
+
+    If either the width or the height of the bounding box is zero (e.g. for a
+    horizontal line), the image side length is applied to the other dimension
+    and the bounding box is centered along the degenerate dimension, see
+    [`Viewport::try_from_bounding_box`].
 
     # Panics
-    Panics if the maximum side length is set to zero.
+    Panics if the bounding box is infinite or if both its width and height are
+    zero. Use [`Viewport::try_from_bounding_box`] to get an error instead.
 
     # Examples
 
@@ -168,219 +528,2232 @@ impl Viewport {
     let viewport = Viewport::from_bounding_box(&bb, SideLength::Width(500));
 
     assert_eq!(viewport.origin, [-6.0, -12.0]); // Negated lower left corner of bb
-    assert_eq!(viewport.scale, 250.0); // 500 / 2
+    assert_eq!(viewport.scale, [250.0, 250.0]); // 500 / 2
     assert_eq!(viewport.width, 500);
     assert_eq!(viewport.height, 2000);
     ```
     */
     pub fn from_bounding_box(bounding_box: &BoundingBox, side_length: SideLength) -> Self {
-        if !bounding_box.is_finite() {
-            panic!("infinite bounding box!")
+        match Self::try_from_bounding_box(bounding_box, side_length) {
+            Ok(viewport) => return viewport,
+            Err(error) => panic!("{error}"),
         }
+    }
+
+    /**
+    Fallible version of [`Viewport::from_bounding_box`].
 
-        let origin = [-bounding_box.xmin(), -bounding_box.ymin()];
+    Returns an error if the bounding box is not finite
+    ([`Error::NonFiniteBoundingBox`]) or if both its width and height are zero
+    ([`Error::DegenerateBoundingBox`]). If only one of the two is zero (e.g. for
+    a horizontal or vertical line), the [`SideLength`] is applied to the other
+    dimension and the degenerate dimension of the image is one unit long. The
+    bounding box is centered along this dimension.
 
-        let [width, height] = side_length.to_width_and_height(bounding_box);
+    # Examples
+
+    ```
+    use cairo_viewport::{Error, Viewport, SideLength};
+    use bounding_box::BoundingBox;
+
+    // Horizontal line
+    let bb = BoundingBox::new(0.0, 4.0, 1.0, 1.0);
+    let viewport = Viewport::try_from_bounding_box(&bb, SideLength::Short(400))
+        .expect("non-zero width is used");
+    assert_eq!(viewport.scale, [100.0, 100.0]);
+    assert_eq!(viewport.width, 400);
+    assert_eq!(viewport.height, 1);
+    assert_eq!(viewport.to_device([0.0, 1.0]), [0.0, 0.5]);
+
+    // Point
+    let bb = BoundingBox::new(1.0, 1.0, 1.0, 1.0);
+    assert!(matches!(
+        Viewport::try_from_bounding_box(&bb, SideLength::Long(400)),
+        Err(Error::DegenerateBoundingBox(_))
+    ));
+
+    // Infinite bounding box
+    let bb = BoundingBox::new(0.0, f64::INFINITY, 1.0, 1.0);
+    assert!(matches!(
+        Viewport::try_from_bounding_box(&bb, SideLength::Long(400)),
+        Err(Error::NonFiniteBoundingBox(_))
+    ));
+    ```
+     */
+    pub fn try_from_bounding_box(
+        bounding_box: &BoundingBox,
+        side_length: SideLength,
+    ) -> Result<Self, Error> {
+        if !bounding_box.is_finite() {
+            return Err(Error::NonFiniteBoundingBox(*bounding_box));
+        }
 
         let width_bb = bounding_box.width();
         let height_bb = bounding_box.height();
+        if width_bb == 0.0 && height_bb == 0.0 {
+            return Err(Error::DegenerateBoundingBox(*bounding_box));
+        }
+
+        let mut origin = [-bounding_box.xmin(), -bounding_box.ymin()];
+
+        let [width, height] = side_length.to_width_and_height(bounding_box);
+
+        // If the height is zero, the ratio is infinite and the width is used.
         let scale = if width_bb / height_bb > 1.0 {
             width as f64 / width_bb
         } else {
             height as f64 / height_bb
         };
 
-        return Viewport {
+        // Center the bounding box along a degenerate dimension
+        if width_bb == 0.0 {
+            origin[0] += 0.5 * width as f64 / scale;
+        }
+        if height_bb == 0.0 {
+            origin[1] += 0.5 * height as f64 / scale;
+        }
+
+        return Ok(Viewport {
             origin,
-            scale,
+            scale: [scale, scale],
             width,
             height,
-        };
+            y_axis: YAxis::Down,
+            rotation: 0.0,
+            debug_overlay: None,
+        });
     }
 
-    /// Draws an image with the given `draw_callback` and saves it into the file
-    /// specified via `path`.
-    ///
-    /// The file type (.pdf, .png, .ps or .svg, see [`CAIRO_FILE_EXTENSIONS`])
-    /// is derived from the file extension specified in `path`. Hence,
-    /// specifying a path without any of these four file extensions results
-    /// in an error.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use cairo_viewport::{Viewport, SideLength};
-    /// use bounding_box::BoundingBox;
-    ///
-    /// let bb = BoundingBox::new(-1.5, 6.5, -3.5, 3.5);
-    /// let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(500));
-    ///
-    /// let draw_callback = |cr: &cairo::Context| {
-    ///
-    ///     // Set the background to white
-    ///     cr.set_source_rgb(1.0, 1.0, 1.0);
-    ///     cr.paint()?;
-    ///
-    ///     cr.set_line_cap(cairo::LineCap::Square);
-    ///
-    ///     // Draw a rectangle
-    ///     cr.move_to(-1.0, -3.0);
-    ///     cr.line_to(6.0, -3.0);
-    ///     cr.line_to(6.0, 3.0);
-    ///     cr.line_to(-1.0, 3.0);
-    ///     cr.close_path();
-    ///     cr.set_line_width(0.1);
-    ///     cr.set_source_rgba(0.0, 0.0, 1.0, 1.0); // Blue line
-    ///    cr.stroke()?;
-    ///
-    ///     // Draw the origin as a black "L" shape
-    ///     cr.move_to(0.0, 0.0);
-    ///     cr.line_to(0.5, 0.0);
-    ///     cr.set_line_width(0.2);
-    ///     cr.set_source_rgba(0.0, 0.0, 0.0, 1.0); // Black line
-    ///     cr.stroke()?;
-    ///
-    ///     cr.move_to(0.0, 0.0);
-    ///     cr.line_to(0.0, 0.5);
-    ///     cr.set_line_width(0.2);
-    ///     cr.set_source_rgba(0.0, 0.0, 0.0, 1.0); // Black line
-    ///     cr.stroke()?;
-    ///
-    ///     return Ok(());
-    /// };
-    ///
-    /// let path = std::path::Path::new("docs/img/rectangle_with_origin.svg");
-    /// assert!(viewport.write_to_file(path, draw_callback).is_ok());
-    /// ```
-    #[doc = ""]
-    #[cfg_attr(
-        feature = "doc-images",
-        doc = "![Rectangle with origin][rectangle_with_origin]"
-    )]
-    #[cfg_attr(
-        feature = "doc-images",
-        embed_doc_image::embed_doc_image(
-            "rectangle_with_origin",
-            "docs/img/rectangle_with_origin.svg"
-        )
-    )]
-    #[cfg_attr(
-        not(feature = "doc-images"),
-        doc = "**Doc images not enabled**. Compile docs with `cargo doc --features 'doc-images'` and Rust version >= 1.54."
-    )]
-    pub fn write_to_file<F, P>(&self, path: P, draw_callback: F) -> Result<(), Error>
-    where
-        F: for<'a> FnOnce(&'a cairo::Context) -> Result<(), cairo::Error>,
-        P: AsRef<Path>,
-    {
-        let path = path.as_ref();
-        let extension = try_get_file_ext_for_cairo(path)?;
+    /**
+    Applies the transformation defined by `self` to the given [`cairo::Context`].
+
+    The current transformation matrix of `cr` is rotated by
+    [`Viewport::rotation`], scaled by [`Viewport::scale`] and then translated by
+    [`Viewport::origin`]. If [`Viewport::y_axis`] is [`YAxis::Up`], the y-axis
+    is additionally flipped around the horizontal center line of the image and
+    the font matrix of `cr` is mirrored so text is still drawn upright (see
+    [`Viewport::set_font_size`]). This is the same transformation which
+    [`Viewport::write_to_file`] applies to the context of the surface it
+    creates. Use this method to draw into surfaces which are not created by
+    [`Viewport`] itself, e.g. a GTK `DrawingArea`, a [`cairo::RecordingSurface`]
+    or any surface handed over by another library.
+
+    Since the transformation is appended to the current transformation matrix,
+    calling this method twice on the same context applies it twice. See
+    [`Viewport::with_context`] for a scoped alternative which restores the
+    previous matrix afterwards.
 
-        // Check if the given path already points to a file. If not, try to create the
-        // file.
-        let mut file = if path.exists() {
-            std::fs::OpenOptions::new().write(true).open(path)?
-        } else {
-            std::fs::File::create(path)?
-        };
+    # Examples
 
-        let (image_surface, cr) = match extension {
-            "ps" => {
-                let surface = cairo::PsSurface::new(self.width.into(), self.height.into(), path)?;
-                let cr = cairo::Context::new(&surface)?;
-                (None, cr)
-            }
-            "png" => {
-                let width = self.width as i32;
-                let height = self.height as i32;
-                let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, width, height)?;
-                let cr = cairo::Context::new(&surface)?;
-                (Some(surface), cr)
-            }
-            "pdf" => {
-                let surface = cairo::PdfSurface::new(self.width.into(), self.height.into(), path)?;
-                let cr = cairo::Context::new(&surface)?;
-                (None, cr)
-            }
-            "svg" => {
-                let surface =
-                    cairo::SvgSurface::new(self.width.into(), self.height.into(), Some(path))?;
-                let cr = cairo::Context::new(&surface)?;
-                (None, cr)
-            }
-            _ => unreachable!("all other possibilites filtered out in try_get_file_ext_for_cairo"),
-        };
+    ```
+    use cairo_viewport::{Viewport, SideLength};
+    use bounding_box::BoundingBox;
 
-        // Adjust the context
-        cr.scale(self.scale, self.scale);
-        cr.translate(self.origin[0], self.origin[1]);
+    let bb = BoundingBox::new(6.0, 8.0, 12.0, 20.0);
+    let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(500));
 
-        // Call the callback to do the actual drawing
-        draw_callback(&cr)?;
+    let surface = cairo::ImageSurface::create(
+        cairo::Format::ARgb32,
+        viewport.width as i32,
+        viewport.height as i32,
+    ).unwrap();
+    let cr = cairo::Context::new(&surface).unwrap();
+    viewport.apply(&cr);
+
+    // The lower left corner of the bounding box is now at the device origin
+    assert_eq!(cr.user_to_device(6.0, 12.0), (0.0, 0.0));
+    assert_eq!(cr.user_to_device(8.0, 20.0), (125.0, 500.0));
+    ```
+     */
+    pub fn apply(&self, cr: &cairo::Context) {
+        cr.transform(self.matrix());
 
-        match image_surface {
-            Some(surface) => surface.write_to_png(&mut file)?,
-            None => (),
+        if self.font_y_factor() != 1.0 {
+            cr.set_font_matrix(self.adjust_font_matrix(cr.font_matrix()));
         }
-
-        return Ok(());
     }
 
     /**
-    A wrapper around [`compare_to_image`] which uses [`Viewport::write_to_file`]
-    as the `draw_callback`.
+    Creates a [`Viewport`] from a [`cairo::Matrix`] which transforms drawing
+    coordinates into image coordinates and the image size.
 
-    Only available if the `image-compare` feature is enabled.
+    This is the inverse of [`Viewport::matrix`]. The matrix is decomposed into
+    [`Viewport::rotation`], [`Viewport::scale`], [`Viewport::origin`] and
+    [`Viewport::y_axis`]. Not every matrix can be represented by a
+    [`Viewport`]: If the matrix is singular or contains a shear,
+    [`Error::UnsupportedMatrix`] is returned. A mirrored x-axis is represented
+    by a rotation of 180° together with a flipped y-axis.
 
     # Examples
 
     ```
-    use cairo_viewport::{compare_to_image, SideLength, Viewport};
+    use cairo_viewport::{Viewport, SideLength, YAxis};
     use bounding_box::BoundingBox;
-    use std::path::Path;
-
-    let bb = BoundingBox::new(-1.0, 1.0, -1.0, 1.0);
-    let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(500));
 
-    fn draw_cross(cr: &cairo::Context, color: [f64; 4]) -> Result<(), cairo::Error> {
+    let bb = BoundingBox::new(0.0, 2.0, 0.0, 1.0);
+    let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(200))
+        .with_y_axis(YAxis::Up);
 
-        // Set the background to white
-        cr.set_source_rgb(1.0, 1.0, 1.0);
-        cr.paint()?;
+    let converted = Viewport::try_from_matrix(viewport.matrix(), viewport.width, viewport.height)
+        .expect("matrix can be represented by a viewport");
+    assert_eq!(converted.y_axis, YAxis::Up);
+    assert_eq!(converted.scale, [100.0, 100.0]);
+    assert_eq!(converted.origin, [0.0, 0.0]);
 
-        cr.move_to(-1.0, 0.0);
-        cr.line_to(1.0, 0.0);
-        cr.set_line_width(0.2);
-        cr.set_source_rgba(color[0], color[1], color[2], color[3]);
-        cr.stroke()?;
+    // Shearing is not supported
+    let shear = cairo::Matrix::new(1.0, 0.0, 0.5, 1.0, 0.0, 0.0);
+    assert!(Viewport::try_from_matrix(shear, 200, 100).is_err());
+    ```
+     */
+    pub fn try_from_matrix(matrix: cairo::Matrix, width: u32, height: u32) -> Result<Self, Error> {
+        let err = || Error::UnsupportedMatrix(matrix);
+
+        // The linear part of the matrix is diag(1, ±1) * diag(sx, sy) * R(rotation).
+        // Hence, the first row of the matrix is sx * [cos, -sin].
+        let scale_x = matrix.xx().hypot(matrix.xy());
+        if !scale_x.is_finite() || scale_x == 0.0 {
+            return Err(err());
+        }
+        let rotation = (-matrix.xy()).atan2(matrix.xx());
+        let (sin, cos) = rotation.sin_cos();
+
+        // The second row is ±sy * [sin, cos]
+        let signed_scale_y = matrix.yx() * sin + matrix.yy() * cos;
+        let residual = matrix.yx() * cos - matrix.yy() * sin;
+        if !signed_scale_y.is_finite()
+            || signed_scale_y == 0.0
+            || residual.abs() > 1e-9 * signed_scale_y.abs().max(scale_x)
+        {
+            return Err(err());
+        }
 
-        cr.move_to(0.0, -1.0);
-        cr.line_to(0.0, 1.0);
-        cr.set_line_width(0.2);
-        cr.set_source_rgba(color[0], color[1], color[2], color[3]);
-        cr.stroke()?;
+        let (y_axis, scale_y, origin_y) = if signed_scale_y > 0.0 {
+            (YAxis::Down, signed_scale_y, matrix.y0() / signed_scale_y)
+        } else {
+            (
+                YAxis::Up,
+                -signed_scale_y,
+                (height as f64 - matrix.y0()) / -signed_scale_y,
+            )
+        };
+
+        return Ok(Viewport {
+            origin: [matrix.x0() / scale_x, origin_y],
+            scale: [scale_x, scale_y],
+            width,
+            height,
+            y_axis,
+            rotation,
+            debug_overlay: None,
+        });
+    }
+
+    /**
+    Returns the [`cairo::Matrix`] which transforms drawing ("world") coordinates
+    into image ("device") coordinates.
+
+    This matrix contains all properties of the viewport which affect the
+    transformation (rotation, scale, origin and y-axis orientation). It is
+    the matrix which [`Viewport::apply`] multiplies onto the transformation
+    matrix of a [`cairo::Context`]. Hence, inside a drawing callback of
+    [`Viewport::write_to_file`], [`cairo::Context::matrix`] equals this matrix.
+    Use [`Viewport::try_from_matrix`] for the inverse conversion.
+
+    # Examples
+
+    ```
+    use cairo_viewport::{Viewport, SideLength};
+    use bounding_box::BoundingBox;
+
+    let bb = BoundingBox::new(-1.0, 1.0, -1.0, 1.0);
+    let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(100));
+
+    let matrix = viewport.matrix();
+    assert_eq!(matrix.transform_point(-1.0, -1.0), (0.0, 0.0));
+    assert_eq!(matrix.transform_point(1.0, 1.0), (100.0, 100.0));
+
+    viewport.write_to_file("docs/img/matrix.svg", |cr| {
+        assert_eq!(cr.matrix(), matrix);
+        return Ok(());
+    }).expect("image can be created");
+    # std::fs::remove_file("docs/img/matrix.svg").unwrap();
+    ```
+     */
+    pub fn matrix(&self) -> cairo::Matrix {
+        let mut matrix = cairo::Matrix::identity();
+        if let YAxis::Up = self.y_axis {
+            matrix.translate(0.0, self.height.into());
+            matrix.scale(1.0, -1.0);
+        }
+        matrix.scale(self.scale[0], self.scale[1]);
+        matrix.translate(self.origin[0], self.origin[1]);
+        if self.rotation != 0.0 {
+            matrix.rotate(self.rotation);
+        }
+        return matrix;
+    }
+
+    /**
+    Converts a `point` from drawing ("world") coordinates into image ("device")
+    coordinates, e.g. pixels for .png.
+
+    This is the transformation which [`Viewport::apply`] adds to a
+    [`cairo::Context`]. See [`Viewport::to_world`] for the inverse operation.
+
+    # Examples
+
+    ```
+    use cairo_viewport::{Viewport, SideLength, YAxis};
+    use bounding_box::BoundingBox;
+
+    let bb = BoundingBox::new(0.0, 2.0, 0.0, 1.0);
+    let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(200));
+    assert_eq!(viewport.to_device([2.0, 0.0]), [200.0, 0.0]);
+
+    let viewport = viewport.with_y_axis(YAxis::Up);
+    assert_eq!(viewport.to_device([2.0, 0.0]), [200.0, 100.0]);
+    ```
+     */
+    pub fn to_device(&self, point: [f64; 2]) -> [f64; 2] {
+        let (x, y) = self.matrix().transform_point(point[0], point[1]);
+        return [x, y];
+    }
+
+    /**
+    Converts a `point` from image ("device") coordinates into drawing ("world")
+    coordinates. This is the inverse of [`Viewport::to_device`] and can e.g. be
+    used to find out which part of a drawing is below the mouse cursor.
+
+    If one of the scale factors is zero, the result is not finite.
+
+    # Examples
+
+    ```
+    use cairo_viewport::{Viewport, SideLength, YAxis};
+    use bounding_box::BoundingBox;
+
+    let bb = BoundingBox::new(0.0, 2.0, 0.0, 1.0);
+    let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(200))
+        .with_y_axis(YAxis::Up);
+    assert_eq!(viewport.to_world([50.0, 25.0]), [0.5, 0.75]);
+    ```
+     */
+    pub fn to_world(&self, point: [f64; 2]) -> [f64; 2] {
+        let [u, mut v] = point;
+        if let YAxis::Up = self.y_axis {
+            v = self.height as f64 - v;
+        }
+        let x = u / self.scale[0] - self.origin[0];
+        let y = v / self.scale[1] - self.origin[1];
+        if self.rotation == 0.0 {
+            return [x, y];
+        }
+        let (sin, cos) = self.rotation.sin_cos();
+        return [cos * x + sin * y, cos * y - sin * x];
+    }
+
+    /**
+    Converts a [`BoundingBox`] from drawing ("world") coordinates into image
+    ("device") coordinates.
+
+    If the viewport is rotated, the returned bounding box is that of the
+    rotated `bounding_box` and therefore larger than the original one.
+
+    # Examples
+
+    ```
+    use cairo_viewport::{Viewport, SideLength};
+    use bounding_box::BoundingBox;
+
+    let bb = BoundingBox::new(0.0, 2.0, 0.0, 1.0);
+    let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(200));
+    let device = viewport.bounding_box_to_device(&BoundingBox::new(0.5, 1.0, 0.0, 0.5));
+    assert_eq!(device, BoundingBox::new(50.0, 100.0, 0.0, 50.0));
+    ```
+     */
+    pub fn bounding_box_to_device(&self, bounding_box: &BoundingBox) -> BoundingBox {
+        return transform_bounding_box(bounding_box, |p| self.to_device(p));
+    }
+
+    /**
+    Converts a [`BoundingBox`] from image ("device") coordinates into drawing
+    ("world") coordinates. This is the inverse of
+    [`Viewport::bounding_box_to_device`] (except for rotated viewports, where
+    both functions enlarge the bounding box).
+     */
+    pub fn bounding_box_to_world(&self, bounding_box: &BoundingBox) -> BoundingBox {
+        return transform_bounding_box(bounding_box, |p| self.to_world(p));
+    }
+
+    /**
+    Returns the [`BoundingBox`] in drawing ("world") coordinates which is
+    covered by the image.
+
+    For a [`Viewport`] created via [`Viewport::from_bounding_box`], this is
+    the original bounding box enlarged by the rounding of the image size. If
+    the viewport is rotated, the returned bounding box is that of the rotated
+    image area.
+
+    # Examples
+
+    ```
+    use cairo_viewport::{Alignment, Fit, Viewport};
+    use bounding_box::BoundingBox;
+
+    let bb = BoundingBox::new(0.0, 2.0, 0.0, 1.0);
+    let viewport = Viewport::from_bounding_box_with_size(&bb, 200, 200, Fit::Contain, Alignment::Center);
+    assert_eq!(viewport.visible_world_bounds(), BoundingBox::new(0.0, 2.0, -0.5, 1.5));
+    ```
+     */
+    pub fn visible_world_bounds(&self) -> BoundingBox {
+        return self.bounding_box_to_world(&BoundingBox::new(
+            0.0,
+            self.width.into(),
+            0.0,
+            self.height.into(),
+        ));
+    }
+
+    /**
+    Checks whether `draw_callback` paints outside of the image area of this
+    viewport, where the content would be silently clipped.
+
+    The callback is recorded into an unbounded
+    [`RecordingSurface`](cairo::RecordingSurface) (with this viewport applied)
+    and its ink extents are measured. If they exceed the image area,
+    [`Error::DrawingOverflow`] is returned, which contains the [`Overflow`] per
+    side in both world and device units. This is e.g. useful in tests to catch
+    drawings whose [`BoundingBox`] does not cover everything that is painted:
+
+    ```
+    use cairo_viewport::{Error, SideLength, Viewport};
+    use bounding_box::BoundingBox;
+
+    let bb = BoundingBox::new(0.0, 2.0, 0.0, 1.0);
+    let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(200));
+
+    // The stroke extends the rectangle by half of its width
+    let result = viewport.check_overflow(|cr| {
+        cr.rectangle(0.0, 0.0, 2.0, 1.0);
+        cr.set_line_width(0.1);
+        return cr.stroke();
+    });
+    let Err(Error::DrawingOverflow(overflow)) = result else {
+        panic!("expected an overflow");
+    };
+    assert_eq!(overflow.device.xmin, 5.0);
+    assert_eq!(overflow.device.ymax, 5.0);
+    assert!((overflow.world.xmax - 0.05).abs() < 1e-9);
+    ```
+
+    The ink extents are determined with pixel precision by rasterizing the
+    recording, hence painting which only partially covers a pixel beyond the
+    image border counts as an overflow of one pixel. Drawings far outside the
+    image (more than 16 million pixels) are measured with cairo's conservative
+    estimate of the ink extents instead. For rotated viewports, the world
+    overflow is measured between the axis-aligned bounding boxes of the
+    painted and the [visible area](Viewport::visible_world_bounds).
+
+    Painting an unbounded area, e.g. a background via
+    [`cairo::Context::paint`] without a clip, cannot be checked and returns
+    [`Error::UnboundedDrawing`]. Fill a rectangle covering
+    [`Viewport::visible_world_bounds`] instead to paint a background.
+
+    The drawing is not written anywhere, so `draw_callback` needs to be called
+    again for the actual output.
+     */
+    pub fn check_overflow<F>(&self, draw_callback: F) -> Result<(), Error>
+    where
+        F: for<'a> FnOnce(&'a cairo::Context) -> Result<(), cairo::Error>,
+    {
+        let recording = cairo::RecordingSurface::create(cairo::Content::ColorAlpha, None)?;
+        // Measure the drawing only, without a possible debug overlay
+        self.draw_without_overlay(&recording, draw_callback)?;
+        let ink = recording_ink_extents(&recording)?;
+        if ink.width() == 0.0 && ink.height() == 0.0 {
+            return Ok(());
+        }
+
+        let width: f64 = self.width.into();
+        let height: f64 = self.height.into();
+        let device = Margin::new(
+            (-ink.xmin()).max(0.0),
+            (ink.xmax() - width).max(0.0),
+            (-ink.ymin()).max(0.0),
+            (ink.ymax() - height).max(0.0),
+            MarginUnit::Device,
+        );
+        if device.xmin == 0.0 && device.xmax == 0.0 && device.ymin == 0.0 && device.ymax == 0.0 {
+            return Ok(());
+        }
+
+        let ink_extents = self.bounding_box_to_world(&ink);
+        let visible = self.visible_world_bounds();
+        let world = Margin::new(
+            (visible.xmin() - ink_extents.xmin()).max(0.0),
+            (ink_extents.xmax() - visible.xmax()).max(0.0),
+            (visible.ymin() - ink_extents.ymin()).max(0.0),
+            (ink_extents.ymax() - visible.ymax()).max(0.0),
+            MarginUnit::World,
+        );
+        return Err(Error::DrawingOverflow(Overflow {
+            world,
+            device,
+            ink_extents,
+        }));
+    }
+
+    /**
+    Factor by which the y-axis of the font matrix needs to be scaled to
+    compensate for a flipped y-axis and non-uniform scaling.
+     */
+    fn font_y_factor(&self) -> f64 {
+        let factor = self.scale[0] / self.scale[1];
+        return match self.y_axis {
+            YAxis::Down => factor,
+            YAxis::Up => -factor,
+        };
+    }
+
+    fn adjust_font_matrix(&self, m: cairo::Matrix) -> cairo::Matrix {
+        let f = self.font_y_factor();
+        return cairo::Matrix::new(m.xx(), f * m.yx(), m.xy(), f * m.yy(), m.x0(), f * m.y0());
+    }
+
+    /**
+    Sets the font size of `cr` to `size` (in user space units) while
+    respecting [`Viewport::y_axis`] and [`Viewport::scale`].
+
+    [`cairo::Context::set_font_size`] replaces the font matrix of the context.
+    For a viewport with [`YAxis::Up`], this undoes the mirroring applied by
+    [`Viewport::apply`] and text is drawn upside down. This method sets a font
+    matrix which is mirrored accordingly. If the scale factors of the x- and
+    y-dimension differ, the font matrix is additionally stretched so that text
+    is not distorted. In that case, `size` is measured in units of the x-axis.
+    For [`YAxis::Down`] and a uniform scale, this method is identical to
+    [`cairo::Context::set_font_size`].
+
+    # Examples
+
+    ```
+    use cairo_viewport::{Viewport, SideLength, YAxis};
+    use bounding_box::BoundingBox;
+
+    let bb = BoundingBox::new(0.0, 10.0, 0.0, 5.0);
+    let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(500))
+        .with_y_axis(YAxis::Up);
+
+    viewport.write_to_file("docs/img/y_axis_up.svg", |cr| {
+        viewport.set_font_size(cr, 2.0);
+        cr.move_to(1.0, 1.0);
+        return cr.show_text("upright");
+    }).expect("image can be created");
+    # std::fs::remove_file("docs/img/y_axis_up.svg").unwrap();
+    ```
+     */
+    pub fn set_font_size(&self, cr: &cairo::Context, size: f64) {
+        cr.set_font_matrix(
+            self.adjust_font_matrix(cairo::Matrix::new(size, 0.0, 0.0, size, 0.0, 0.0)),
+        );
+    }
+
+    /**
+    Applies `self` to the given [`cairo::Context`] (see [`Viewport::apply`]),
+    calls `draw_callback` with it and restores the previous state of the
+    context afterwards.
+
+    The state of `cr` (including its transformation matrix) is saved via
+    [`cairo::Context::save`] before the viewport is applied and restored via
+    [`cairo::Context::restore`] after `draw_callback` returned. The state is
+    restored even if `draw_callback` returns an error. If
+    [`Viewport::debug_overlay`] is set, the overlay is drawn afterwards in the
+    restored state.
+
+    # Examples
+
+    ```
+    use cairo_viewport::{Viewport, SideLength};
+    use bounding_box::BoundingBox;
+
+    let bb = BoundingBox::new(-1.0, 1.0, -1.0, 1.0);
+    let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(100));
+
+    let surface = cairo::RecordingSurface::create(cairo::Content::ColorAlpha, None).unwrap();
+    let cr = cairo::Context::new(&surface).unwrap();
+    let matrix_before = cr.matrix();
+
+    viewport.with_context(&cr, |cr| {
+        assert_eq!(cr.user_to_device(-1.0, -1.0), (0.0, 0.0));
+        cr.arc(0.0, 0.0, 0.5, 0.0, 2.0 * std::f64::consts::PI);
+        return cr.fill();
+    }).expect("drawing succeeded");
+
+    assert_eq!(cr.matrix(), matrix_before);
+    ```
+     */
+    pub fn with_context<F>(&self, cr: &cairo::Context, draw_callback: F) -> Result<(), Error>
+    where
+        F: for<'a> FnOnce(&'a cairo::Context) -> Result<(), cairo::Error>,
+    {
+        cr.save()?;
+        self.apply(cr);
+        let res = draw_callback(cr);
+        cr.restore()?;
+        res?;
+        if let Some(overlay) = &self.debug_overlay {
+            cr.save()?;
+            let res = overlay.draw(self, cr);
+            cr.restore()?;
+            res?;
+        }
+        return Ok(());
+    }
+
+    /**
+    Adds a clickable link area to the current page of `cr`.
+
+    The `area` is given in drawing ("world") coordinates and converted into the
+    image ("device") rectangle expected by cairo's tag API via
+    [`Viewport::bounding_box_to_device`]. Hence, `cr` needs to draw onto a
+    surface configured by `self`, e.g. inside the callback of
+    [`Viewport::write_to_file`] or [`ViewportDocument::add_page`]. Links are
+    only supported by .pdf files, all other formats ignore them.
+
+    # Examples
+
+    ```
+    use cairo_viewport::{LinkTarget, OutputFormat, Viewport, SideLength};
+    use bounding_box::BoundingBox;
+
+    let bb = BoundingBox::new(0.0, 2.0, 0.0, 1.0);
+    let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(200));
+
+    let pdf = viewport.render_to_vec(OutputFormat::Pdf, |cr| {
+        cr.rectangle(0.5, 0.25, 1.0, 0.5);
+        cr.fill()?;
+        let target = LinkTarget::Uri("https://www.cairographics.org".into());
+        return viewport.add_link(cr, &BoundingBox::new(0.5, 1.5, 0.25, 0.75), &target);
+    }).unwrap();
+    assert!(pdf.starts_with(b"%PDF"));
+    ```
+     */
+    pub fn add_link(
+        &self,
+        cr: &cairo::Context,
+        area: &BoundingBox,
+        target: &LinkTarget,
+    ) -> Result<(), cairo::Error> {
+        let rect = self.bounding_box_to_device(area);
+        let target = match target {
+            LinkTarget::Uri(uri) => format!("uri='{}'", escape_tag_attribute(uri)),
+            LinkTarget::Page(page) => format!("page={}", page + 1),
+        };
+        let attributes = format!(
+            "rect=[{} {} {} {}] {target}",
+            rect.xmin(),
+            rect.ymin(),
+            rect.width(),
+            rect.height()
+        );
+        cr.tag_begin(cairo::CAIRO_TAG_LINK, &attributes);
+        cr.tag_end(cairo::CAIRO_TAG_LINK);
+        return cr.status();
+    }
+
+    /**
+    Creates a [`Viewport`] which shows the given [`BoundingBox`] rotated by
+    `rotation` (in radians, see [`Viewport::rotation`]).
+
+    The image size is calculated via [`SideLength::to_width_and_height`] from
+    the bounding box of the rotated `bounding_box`. Therefore, the rotated
+    drawing fills the image exactly. For a rotation of zero, this function is
+    identical to [`Viewport::from_bounding_box`].
+
+    # Panics
+    Panics if the bounding box is infinite or if both width and height of the
+    rotated bounding box are zero. Use
+    [`Viewport::try_from_bounding_box_with_rotation`] to get an error instead.
+
+    # Examples
+
+    ```
+    use cairo_viewport::{Viewport, SideLength};
+    use bounding_box::BoundingBox;
+    use std::f64::consts::FRAC_PI_2;
+
+    // A landscape drawing rotated into a portrait image
+    let bb = BoundingBox::new(0.0, 4.0, 0.0, 2.0);
+    let viewport = Viewport::from_bounding_box_with_rotation(&bb, SideLength::Long(400), FRAC_PI_2);
+    assert_eq!(viewport.width, 200);
+    assert_eq!(viewport.height, 400);
+
+    let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, 200, 400).unwrap();
+    let cr = cairo::Context::new(&surface).unwrap();
+    viewport.apply(&cr);
+
+    // The origin of the drawing is now in the upper right corner
+    let (x, y) = cr.user_to_device(0.0, 0.0);
+    assert!((x - 200.0).abs() < 1e-9);
+    assert!(y.abs() < 1e-9);
+    ```
+     */
+    pub fn from_bounding_box_with_rotation(
+        bounding_box: &BoundingBox,
+        side_length: SideLength,
+        rotation: f64,
+    ) -> Self {
+        match Self::try_from_bounding_box_with_rotation(bounding_box, side_length, rotation) {
+            Ok(viewport) => return viewport,
+            Err(error) => panic!("{error}"),
+        }
+    }
+
+    /**
+    Fallible version of [`Viewport::from_bounding_box_with_rotation`].
+
+    Returns [`Error::NonFiniteBoundingBox`] if the bounding box is not finite
+    and [`Error::DegenerateBoundingBox`] if both width and height of the
+    rotated bounding box are zero.
+     */
+    pub fn try_from_bounding_box_with_rotation(
+        bounding_box: &BoundingBox,
+        side_length: SideLength,
+        rotation: f64,
+    ) -> Result<Self, Error> {
+        if !bounding_box.is_finite() {
+            return Err(Error::NonFiniteBoundingBox(*bounding_box));
+        }
+        let mut viewport =
+            Self::try_from_bounding_box(&rotate_bounding_box(bounding_box, rotation), side_length)?;
+        viewport.rotation = rotation;
+        return Ok(viewport);
+    }
+
+    /**
+    Creates a [`Viewport`] from a given [`BoundingBox`] and separate scale
+    factors for the x- and y-dimension.
+
+    The image size is calculated so that the bounding box exactly fills the
+    image, i.e. `width = ceil(bounding_box.width() * scale[0])` and
+    `height = ceil(bounding_box.height() * scale[1])`. This is useful if the
+    coordinates of the drawing have different units, e.g. seconds on the x-axis
+    and millimeters on the y-axis of a time series. See
+    [`Viewport::from_bounding_box_with_size`] together with [`Fit::Stretch`] for
+    a fixed image size instead.
+
+    If either the width or the height of the bounding box is zero, the
+    corresponding image side is one unit long and the bounding box is centered
+    along it.
+
+    # Panics
+    Panics if the bounding box is infinite or if both its width and height are
+    zero. Use [`Viewport::try_from_bounding_box_with_scale`] to get an error
+    instead.
+
+    # Examples
+
+    ```
+    use cairo_viewport::Viewport;
+    use bounding_box::BoundingBox;
+
+    // 10 seconds on the x-axis, 2 millimeters on the y-axis
+    let bb = BoundingBox::new(0.0, 10.0, -1.0, 1.0);
+
+    // 50 pixel per second, 100 pixel per millimeter
+    let viewport = Viewport::from_bounding_box_with_scale(&bb, [50.0, 100.0]);
+    assert_eq!(viewport.origin, [0.0, 1.0]);
+    assert_eq!(viewport.width, 500);
+    assert_eq!(viewport.height, 200);
+    ```
+     */
+    pub fn from_bounding_box_with_scale(bounding_box: &BoundingBox, scale: [f64; 2]) -> Self {
+        match Self::try_from_bounding_box_with_scale(bounding_box, scale) {
+            Ok(viewport) => return viewport,
+            Err(error) => panic!("{error}"),
+        }
+    }
+
+    /**
+    Fallible version of [`Viewport::from_bounding_box_with_scale`].
+
+    Returns [`Error::NonFiniteBoundingBox`] if the bounding box is not finite
+    and [`Error::DegenerateBoundingBox`] if both its width and height are zero.
+     */
+    pub fn try_from_bounding_box_with_scale(
+        bounding_box: &BoundingBox,
+        scale: [f64; 2],
+    ) -> Result<Self, Error> {
+        if !bounding_box.is_finite() {
+            return Err(Error::NonFiniteBoundingBox(*bounding_box));
+        }
+        let width_bb = bounding_box.width();
+        let height_bb = bounding_box.height();
+        if width_bb == 0.0 && height_bb == 0.0 {
+            return Err(Error::DegenerateBoundingBox(*bounding_box));
+        }
+
+        let width = ((width_bb * scale[0]).ceil() as u32).max(1);
+        let height = ((height_bb * scale[1]).ceil() as u32).max(1);
+        let mut origin = [-bounding_box.xmin(), -bounding_box.ymin()];
+
+        // Center the bounding box along a degenerate dimension
+        if width_bb == 0.0 {
+            origin[0] += 0.5 * width as f64 / scale[0];
+        }
+        if height_bb == 0.0 {
+            origin[1] += 0.5 * height as f64 / scale[1];
+        }
+
+        return Ok(Viewport {
+            origin,
+            scale,
+            width,
+            height,
+            y_axis: YAxis::Down,
+            rotation: 0.0,
+            debug_overlay: None,
+        });
+    }
+
+    /**
+    Creates a [`Viewport`] from a given [`BoundingBox`], the specified
+    [`SideLength`] and an additional [`Margin`] around the bounding box.
+
+    Strokes drawn along the edge of a [`BoundingBox`] are clipped by half of
+    their width, since only the area inside the box is shown in the image. A
+    [`Margin`] adds some padding around the bounding box to avoid this. It can
+    be specified either in world units ([`MarginUnit::World`]), where it is
+    simply added to the bounding box before calling
+    [`Viewport::from_bounding_box`], or in the units of the image
+    ([`MarginUnit::Device`], e.g. pixels for .png).
+
+    In the latter case, [`SideLength`] refers to the full image including the
+    margins. The bounding box is then scaled to fill the remaining area. Since
+    the sides of the margin are given for the extremal values of the bounding
+    box (e.g. [`Margin::ymin`] is the margin next to `bounding_box.ymin()`),
+    the resulting viewport is valid for both [`YAxis`] orientations.
+
+    # Panics
+    Panics if the bounding box is infinite, if both its width and height are
+    zero or if the margins are larger than the image side length specified via
+    `side_length`. Use [`Viewport::try_from_bounding_box_with_margin`] to get an
+    error instead.
+
+    # Examples
+
+    ```
+    use cairo_viewport::{Viewport, SideLength, Margin};
+    use bounding_box::BoundingBox;
+
+    let bb = BoundingBox::new(0.0, 4.0, 0.0, 2.0);
+
+    // Margin in world units: identical to a bounding box which is 0.5 units
+    // larger on each side.
+    let viewport = Viewport::from_bounding_box_with_margin(
+        &bb,
+        SideLength::Width(500),
+        Margin::world(0.5),
+    );
+    assert_eq!(viewport.origin, [0.5, 0.5]);
+    assert_eq!(viewport.scale, [100.0, 100.0]);
+    assert_eq!(viewport.width, 500);
+    assert_eq!(viewport.height, 300);
+
+    // Margin of 10 pixels on each side: The bounding box fills 480 pixels.
+    let viewport = Viewport::from_bounding_box_with_margin(
+        &bb,
+        SideLength::Width(500),
+        Margin::device(10.0),
+    );
+    assert_eq!(viewport.scale, [120.0, 120.0]);
+    assert_eq!(viewport.width, 500);
+    assert_eq!(viewport.height, 260);
+
+    let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, 500, 260).unwrap();
+    let cr = cairo::Context::new(&surface).unwrap();
+    viewport.apply(&cr);
+    assert_eq!(cr.user_to_device(0.0, 0.0), (10.0, 10.0));
+    assert_eq!(cr.user_to_device(4.0, 2.0), (490.0, 250.0));
+    ```
+     */
+    pub fn from_bounding_box_with_margin(
+        bounding_box: &BoundingBox,
+        side_length: SideLength,
+        margin: Margin,
+    ) -> Self {
+        match Self::try_from_bounding_box_with_margin(bounding_box, side_length, margin) {
+            Ok(viewport) => return viewport,
+            Err(error) => panic!("{error}"),
+        }
+    }
+
+    /**
+    Fallible version of [`Viewport::from_bounding_box_with_margin`].
+
+    Returns an error if the bounding box is not finite
+    ([`Error::NonFiniteBoundingBox`]), if both its width and height are zero
+    ([`Error::DegenerateBoundingBox`]) or if the margins leave no space for the
+    bounding box ([`Error::MarginsExceedSideLength`]).
+
+    For [`MarginUnit::Device`], the image side selected by [`SideLength`] is
+    determined from the full image size including the margins. If only the
+    width or the height of the bounding box is zero, the side length is applied
+    to the other dimension and the bounding box is centered along the
+    degenerate dimension, as in [`Viewport::try_from_bounding_box`].
+
+    # Examples
+
+    ```
+    use cairo_viewport::{Error, Margin, MarginUnit, SideLength, Viewport};
+    use bounding_box::BoundingBox;
+
+    // Large margins at the top and bottom turn the landscape bounding box into
+    // a portrait image, whose long side is its height.
+    let bb = BoundingBox::new(0.0, 4.0, 0.0, 2.0);
+    let margin = Margin::new(0.0, 0.0, 200.0, 200.0, MarginUnit::Device);
+    let viewport =
+        Viewport::try_from_bounding_box_with_margin(&bb, SideLength::Long(500), margin).unwrap();
+    assert_eq!(viewport.scale, [50.0, 50.0]);
+    assert_eq!(viewport.width, 200);
+    assert_eq!(viewport.height, 500);
+
+    assert!(matches!(
+        Viewport::try_from_bounding_box_with_margin(&bb, SideLength::Width(100), Margin::device(50.0)),
+        Err(Error::MarginsExceedSideLength(_))
+    ));
+    ```
+     */
+    pub fn try_from_bounding_box_with_margin(
+        bounding_box: &BoundingBox,
+        side_length: SideLength,
+        margin: Margin,
+    ) -> Result<Self, Error> {
+        if !bounding_box.is_finite() {
+            return Err(Error::NonFiniteBoundingBox(*bounding_box));
+        }
+
+        let width_bb = bounding_box.width();
+        let height_bb = bounding_box.height();
+        if width_bb == 0.0 && height_bb == 0.0 {
+            return Err(Error::DegenerateBoundingBox(*bounding_box));
+        }
+
+        match margin.unit {
+            MarginUnit::World => {
+                let padded = BoundingBox::new(
+                    bounding_box.xmin() - margin.xmin,
+                    bounding_box.xmax() + margin.xmax,
+                    bounding_box.ymin() - margin.ymin,
+                    bounding_box.ymax() + margin.ymax,
+                );
+                return Self::try_from_bounding_box(&padded, side_length);
+            }
+            MarginUnit::Device => {
+                let margin_x = margin.xmin + margin.xmax;
+                let margin_y = margin.ymin + margin.ymax;
+                let length = f64::from(u32::from(side_length));
+
+                // Scale of the bounding box if the image width (true) or height
+                // (false) equals the side length
+                let scale_for = |fixed_width: bool| -> f64 {
+                    if fixed_width {
+                        return (length - margin_x) / width_bb;
+                    }
+                    return (length - margin_y) / height_bb;
+                };
+
+                // Find out which side of the image is fixed. For Long and Short,
+                // fixing the width is correct if the resulting image height
+                // (including the margins) is shorter or longer respectively.
+                let fixed_width = if height_bb == 0.0 {
+                    true
+                } else if width_bb == 0.0 {
+                    false
+                } else {
+                    match side_length {
+                        SideLength::Long(_) => height_bb * scale_for(true) + margin_y <= length,
+                        SideLength::Short(_) => height_bb * scale_for(true) + margin_y >= length,
+                        SideLength::Width(_) => true,
+                        SideLength::Height(_) => false,
+                    }
+                };
+                let scale = scale_for(fixed_width);
+                if !(scale.is_finite() && scale > 0.0) {
+                    return Err(Error::MarginsExceedSideLength(margin));
+                }
+
+                let (width, height) = if fixed_width {
+                    (length, (height_bb * scale + margin_y).ceil().max(1.0))
+                } else {
+                    ((width_bb * scale + margin_x).ceil().max(1.0), length)
+                };
+
+                let mut origin = [
+                    margin.xmin / scale - bounding_box.xmin(),
+                    margin.ymin / scale - bounding_box.ymin(),
+                ];
+
+                // Center the bounding box along a degenerate dimension
+                if width_bb == 0.0 {
+                    origin[0] += 0.5 * (width - margin_x) / scale;
+                }
+                if height_bb == 0.0 {
+                    origin[1] += 0.5 * (height - margin_y) / scale;
+                }
+
+                return Ok(Viewport {
+                    origin,
+                    scale: [scale, scale],
+                    width: width as u32,
+                    height: height as u32,
+                    y_axis: YAxis::Down,
+                    rotation: 0.0,
+                    debug_overlay: None,
+                });
+            }
+        }
+    }
+
+    /**
+    Creates a [`Viewport`] which fits the area actually painted by
+    `draw_callback`.
+
+    Implementing a correct bounding box for every drawing is error-prone, since
+    e.g. stroke widths, arrowheads and text are easily forgotten. This function
+    instead records `draw_callback` once into a [`RecordedDrawing`], measures
+    its ink extents (see [`RecordedDrawing::ink_extents`]) and forwards them
+    together with `side_length` and `margin` to
+    [`Viewport::from_bounding_box_with_margin`]. The returned
+    [`RecordedDrawing`] can then be replayed into the final output via
+    [`RecordedDrawing::replay`], so `draw_callback` is called only once.
+
+    Returns [`Error::DegenerateBoundingBox`] if `draw_callback` did not paint
+    anything and [`Error::UnboundedDrawing`] if it painted an unbounded area
+    (e.g. a background via [`cairo::Context::paint`]).
+
+    # Examples
+
+    ```
+    use cairo_viewport::{Margin, SideLength, Viewport};
+
+    let (viewport, drawing) = Viewport::fit_to_drawing(
+        |cr| {
+            // The stroke extends the drawing by half of its width
+            cr.rectangle(0.0, 0.0, 4.0, 2.0);
+            cr.set_line_width(1.0);
+            return cr.stroke();
+        },
+        SideLength::Long(500),
+        Margin::device(10.0),
+    )
+    .unwrap();
+    assert_eq!(viewport.width, 500);
+    assert_eq!(viewport.height, 308);
+    let [x, y] = viewport.to_device([-0.5, -0.5]);
+    assert!((x - 10.0).abs() < 1e-9 && (y - 10.0).abs() < 1e-9);
+
+    let path = std::env::temp_dir().join("cairo_viewport_fit_to_drawing.svg");
+    viewport.write_to_file(&path, |cr| drawing.replay(cr)).unwrap();
+    # std::fs::remove_file(&path).unwrap();
+    ```
+     */
+    pub fn fit_to_drawing<F>(
+        draw_callback: F,
+        side_length: SideLength,
+        margin: Margin,
+    ) -> Result<(Self, RecordedDrawing), Error>
+    where
+        F: for<'a> FnOnce(&'a cairo::Context) -> Result<(), cairo::Error>,
+    {
+        let drawing = RecordedDrawing::new(draw_callback)?;
+        let ink = drawing.ink_extents()?;
+        if ink.width() == 0.0 && ink.height() == 0.0 {
+            return Err(Error::DegenerateBoundingBox(ink));
+        }
+        let viewport = Self::try_from_bounding_box_with_margin(&ink, side_length, margin)?;
+        return Ok((viewport, drawing));
+    }
+
+    /**
+    Creates a [`Viewport`] with a fixed `width` and `height` which shows the
+    given [`BoundingBox`].
+
+    In contrast to [`Viewport::from_bounding_box`], the aspect ratio of the
+    image is independent of that of the bounding box. The [`Fit`] mode defines
+    how the bounding box is scaled to the image size, while [`Alignment`] defines
+    where it is placed if it does not fill the image exactly (or where it is
+    cropped in case of [`Fit::Cover`]). This is a shorthand for creating a
+    [`Viewport`] with the given size and then calling
+    [`Viewport::fit_bounding_box`].
+
+    # Panics
+    Panics if the bounding box is infinite or if both its width and height are
+    zero. Use [`Viewport::try_from_bounding_box_with_size`] to get an error
+    instead.
+
+    # Examples
+
+    ```
+    use cairo_viewport::{Alignment, Fit, Viewport};
+    use bounding_box::BoundingBox;
+
+    let bb = BoundingBox::new(0.0, 2.0, 0.0, 1.0);
+
+    // The bounding box is fully visible and centered in a square image
+    let viewport = Viewport::from_bounding_box_with_size(&bb, 512, 512, Fit::Contain, Alignment::Center);
+    assert_eq!(viewport.scale, [256.0, 256.0]);
+    assert_eq!(viewport.origin, [0.0, 0.5]);
+
+    // The bounding box fills the entire image, its right side is cropped
+    let viewport = Viewport::from_bounding_box_with_size(&bb, 512, 512, Fit::Cover, Alignment::Left);
+    assert_eq!(viewport.scale, [512.0, 512.0]);
+    assert_eq!(viewport.origin, [0.0, 0.0]);
+
+    // The bounding box is distorted to fill the image exactly
+    let viewport = Viewport::from_bounding_box_with_size(&bb, 512, 512, Fit::Stretch, Alignment::Center);
+    assert_eq!(viewport.scale, [256.0, 512.0]);
+    assert_eq!(viewport.origin, [0.0, 0.0]);
+    ```
+     */
+    pub fn from_bounding_box_with_size(
+        bounding_box: &BoundingBox,
+        width: u32,
+        height: u32,
+        fit: Fit,
+        alignment: Alignment,
+    ) -> Self {
+        return Self::new([0.0, 0.0], [1.0, 1.0], width, height).fit_bounding_box(
+            bounding_box,
+            fit,
+            alignment,
+        );
+    }
+
+    /**
+    Fallible version of [`Viewport::from_bounding_box_with_size`], see
+    [`Viewport::try_fit_bounding_box`].
+     */
+    pub fn try_from_bounding_box_with_size(
+        bounding_box: &BoundingBox,
+        width: u32,
+        height: u32,
+        fit: Fit,
+        alignment: Alignment,
+    ) -> Result<Self, Error> {
+        return Self::new([0.0, 0.0], [1.0, 1.0], width, height).try_fit_bounding_box(
+            bounding_box,
+            fit,
+            alignment,
+        );
+    }
+
+    /**
+    Returns `self` with [`Viewport::origin`] and [`Viewport::scale`] adjusted
+    so that the given [`BoundingBox`] is shown in the image according to the
+    [`Fit`] and [`Alignment`].
+
+    The size of the image as well as [`Viewport::y_axis`] and
+    [`Viewport::rotation`] are kept. If the viewport is rotated, the bounding
+    box of the rotated `bounding_box` is fitted into the image. The
+    [`Alignment`] always refers to the sides of the image, i.e.
+    [`Alignment::Top`] places the bounding box at the top of the image for both
+    [`YAxis`] orientations. Therefore, [`Viewport::with_y_axis`] needs to be
+    called before this method.
+
+    If either the width or the height of the (rotated) bounding box is zero,
+    its other dimension determines the scale for all [`Fit`] modes.
+
+    # Panics
+    Panics if the bounding box is infinite or if both width and height of the
+    (rotated) bounding box are zero. Use [`Viewport::try_fit_bounding_box`] to
+    get an error instead.
+
+    # Examples
+
+    ```
+    use cairo_viewport::{Alignment, Fit, Viewport, YAxis};
+    use bounding_box::BoundingBox;
+
+    let bb = BoundingBox::new(0.0, 2.0, 0.0, 1.0);
+    let viewport = Viewport::new([0.0, 0.0], [1.0, 1.0], 200, 200)
+        .with_y_axis(YAxis::Up)
+        .fit_bounding_box(&bb, Fit::Contain, Alignment::Top);
+
+    let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, 200, 200).unwrap();
+    let cr = cairo::Context::new(&surface).unwrap();
+    viewport.apply(&cr);
+
+    // The upper left corner of the bounding box is the upper left corner of the image
+    assert_eq!(cr.user_to_device(0.0, 1.0), (0.0, 0.0));
+    assert_eq!(cr.user_to_device(2.0, 0.0), (200.0, 100.0));
+    ```
+     */
+    pub fn fit_bounding_box(
+        self,
+        bounding_box: &BoundingBox,
+        fit: Fit,
+        alignment: Alignment,
+    ) -> Self {
+        match self.try_fit_bounding_box(bounding_box, fit, alignment) {
+            Ok(viewport) => return viewport,
+            Err(error) => panic!("{error}"),
+        }
+    }
+
+    /**
+    Fallible version of [`Viewport::fit_bounding_box`].
+
+    Returns [`Error::NonFiniteBoundingBox`] if the bounding box is not finite
+    and [`Error::DegenerateBoundingBox`] if both width and height of the
+    (rotated) bounding box are zero.
+     */
+    pub fn try_fit_bounding_box(
+        mut self,
+        bounding_box: &BoundingBox,
+        fit: Fit,
+        alignment: Alignment,
+    ) -> Result<Self, Error> {
+        if !bounding_box.is_finite() {
+            return Err(Error::NonFiniteBoundingBox(*bounding_box));
+        }
+        let bounding_box = &rotate_bounding_box(bounding_box, self.rotation);
+        if bounding_box.width() == 0.0 && bounding_box.height() == 0.0 {
+            return Err(Error::DegenerateBoundingBox(*bounding_box));
+        }
+
+        let width = self.width as f64;
+        let height = self.height as f64;
+        let mut scale_x = width / bounding_box.width();
+        let mut scale_y = height / bounding_box.height();
+
+        // A degenerate dimension does not restrict the scale
+        if bounding_box.width() == 0.0 {
+            scale_x = scale_y;
+        } else if bounding_box.height() == 0.0 {
+            scale_y = scale_x;
+        }
+        self.scale = match fit {
+            Fit::Contain => [scale_x.min(scale_y); 2],
+            Fit::Cover => [scale_x.max(scale_y); 2],
+            Fit::Stretch => [scale_x, scale_y],
+        };
+
+        // Unused space in the image (negative if the bounding box is cropped)
+        let slack_x = width - self.scale[0] * bounding_box.width();
+        let slack_y = height - self.scale[1] * bounding_box.height();
+        let [align_x, mut align_y] = alignment.factors();
+        if let YAxis::Up = self.y_axis {
+            align_y = 1.0 - align_y;
+        }
+
+        self.origin = [
+            align_x * slack_x / self.scale[0] - bounding_box.xmin(),
+            align_y * slack_y / self.scale[1] - bounding_box.ymin(),
+        ];
+        return Ok(self);
+    }
+
+    /**
+    Moves the drawing by `dx` and `dy` image units (e.g. pixels for .png).
+
+    Positive values move the drawing to the right and downwards in the image,
+    independent of [`Viewport::y_axis`] and [`Viewport::rotation`]. This is the
+    typical "drag" operation of an interactive viewer.
+
+    # Examples
+
+    ```
+    use cairo_viewport::{Viewport, SideLength, YAxis};
+    use bounding_box::BoundingBox;
+
+    let bb = BoundingBox::new(0.0, 2.0, 0.0, 1.0);
+    let mut viewport = Viewport::from_bounding_box(&bb, SideLength::Long(200))
+        .with_y_axis(YAxis::Up);
+    assert_eq!(viewport.to_device([0.0, 0.0]), [0.0, 100.0]);
+
+    viewport.pan_by_pixels(10.0, -20.0);
+    assert_eq!(viewport.to_device([0.0, 0.0]), [10.0, 80.0]);
+    ```
+     */
+    pub fn pan_by_pixels(&mut self, dx: f64, dy: f64) {
+        self.origin[0] += dx / self.scale[0];
+        match self.y_axis {
+            YAxis::Down => self.origin[1] += dy / self.scale[1],
+            YAxis::Up => self.origin[1] -= dy / self.scale[1],
+        }
+    }
+
+    /**
+    Scales the drawing by `factor` while keeping the drawing coordinates
+    below `device_point` (given in image units) fixed.
+
+    A factor larger than one zooms in, a factor smaller than one zooms out.
+    This is the typical "zoom with the mouse wheel" operation of an interactive
+    viewer, where `device_point` is the position of the mouse cursor.
+
+    # Examples
+
+    ```
+    use cairo_viewport::{Viewport, SideLength};
+    use bounding_box::BoundingBox;
+
+    let bb = BoundingBox::new(0.0, 2.0, 0.0, 1.0);
+    let mut viewport = Viewport::from_bounding_box(&bb, SideLength::Long(200));
+    let world = viewport.to_world([50.0, 20.0]);
+
+    viewport.zoom_at([50.0, 20.0], 2.0);
+    assert_eq!(viewport.scale, [200.0, 200.0]);
+    assert_eq!(viewport.to_device(world), [50.0, 20.0]);
+    ```
+     */
+    pub fn zoom_at(&mut self, device_point: [f64; 2], factor: f64) {
+        let world = self.to_world(device_point);
+        self.scale = [self.scale[0] * factor, self.scale[1] * factor];
+        self.pan_to(world, device_point);
+    }
+
+    /**
+    Adjusts [`Viewport::origin`] and [`Viewport::scale`] so that the given
+    [`BoundingBox`] is fully visible and centered in the image.
+
+    The image size, [`Viewport::y_axis`] and [`Viewport::rotation`] are kept.
+    This is a shorthand for [`Viewport::fit_bounding_box`] with [`Fit::Contain`]
+    and [`Alignment::Center`].
+
+    # Panics
+    Panics if the bounding box is infinite or if both width and height of the
+    (rotated) bounding box are zero. Use [`Viewport::try_zoom_to_bounding_box`]
+    to get an error instead.
+
+    # Examples
+
+    ```
+    use cairo_viewport::{Viewport, SideLength};
+    use bounding_box::BoundingBox;
+
+    let bb = BoundingBox::new(0.0, 2.0, 0.0, 1.0);
+    let mut viewport = Viewport::from_bounding_box(&bb, SideLength::Long(200));
+
+    viewport.zoom_to_bounding_box(&BoundingBox::new(0.0, 0.5, 0.0, 0.5));
+    assert_eq!(viewport.scale, [200.0, 200.0]);
+    assert_eq!(viewport.visible_world_bounds(), BoundingBox::new(-0.25, 0.75, 0.0, 0.5));
+    ```
+     */
+    pub fn zoom_to_bounding_box(&mut self, bounding_box: &BoundingBox) {
+        if let Err(error) = self.try_zoom_to_bounding_box(bounding_box) {
+            panic!("{error}");
+        }
+    }
+
+    /**
+    Fallible version of [`Viewport::zoom_to_bounding_box`].
+
+    Returns [`Error::NonFiniteBoundingBox`] if the bounding box is not finite
+    and [`Error::DegenerateBoundingBox`] if both width and height of the
+    (rotated) bounding box are zero. In both cases, the viewport is left
+    unchanged.
+     */
+    pub fn try_zoom_to_bounding_box(&mut self, bounding_box: &BoundingBox) -> Result<(), Error> {
+        *self = self.try_fit_bounding_box(bounding_box, Fit::Contain, Alignment::Center)?;
+        return Ok(());
+    }
+
+    /**
+    Changes the image size to `width` and `height` without changing the scale.
+
+    The drawing coordinates which were shown at the `anchor` point of the
+    image (e.g. its center for [`Alignment::Center`]) are shown at the `anchor`
+    point of the resized image. This is useful to react to resizing the window
+    of an interactive viewer.
+
+    # Examples
+
+    ```
+    use cairo_viewport::{Alignment, Viewport, SideLength};
+    use bounding_box::BoundingBox;
+
+    let bb = BoundingBox::new(0.0, 2.0, 0.0, 1.0);
+    let mut viewport = Viewport::from_bounding_box(&bb, SideLength::Long(200));
+
+    viewport.resize(400, 300, Alignment::Center);
+    assert_eq!(viewport.scale, [100.0, 100.0]);
+    assert_eq!(viewport.to_device([1.0, 0.5]), [200.0, 150.0]);
+
+    viewport.resize(100, 100, Alignment::TopLeft);
+    assert_eq!(viewport.to_device([1.0, 0.5]), [200.0, 150.0]);
+    ```
+     */
+    pub fn resize(&mut self, width: u32, height: u32, anchor: Alignment) {
+        let [fx, fy] = anchor.factors();
+        let world = self.to_world([fx * self.width as f64, fy * self.height as f64]);
+        self.width = width;
+        self.height = height;
+        self.pan_to(world, [fx * width as f64, fy * height as f64]);
+    }
+
+    /**
+    Moves the drawing so that `world_point` is shown at `device_point`.
+     */
+    fn pan_to(&mut self, world_point: [f64; 2], device_point: [f64; 2]) {
+        let current = self.to_device(world_point);
+        self.pan_by_pixels(device_point[0] - current[0], device_point[1] - current[1]);
+    }
+
+    /// Draws an image with the given `draw_callback` and saves it into the file
+    /// specified via `path`.
+    ///
+    /// The file type (.pdf, .png, .ps, .eps or .svg and, with the `image`
+    /// feature, .jpg, .webp, .tiff, .bmp or .gif, see
+    /// [`CAIRO_FILE_EXTENSIONS`]) is derived from the file extension specified
+    /// in `path` (case-insensitive, see [`OutputFormat::from_path`]). Hence,
+    /// specifying a path without any of these file extensions results in an
+    /// error. Use [`Viewport::write_to_file_as`] to specify the format
+    /// explicitly. An existing file is replaced atomically, see
+    /// [`Viewport::write_to_file_as`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cairo_viewport::{Viewport, SideLength};
+    /// use bounding_box::BoundingBox;
+    ///
+    /// let bb = BoundingBox::new(-1.5, 6.5, -3.5, 3.5);
+    /// let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(500));
+    ///
+    /// let draw_callback = |cr: &cairo::Context| {
+    ///
+    ///     // Set the background to white
+    ///     cr.set_source_rgb(1.0, 1.0, 1.0);
+    ///     cr.paint()?;
+    ///
+    ///     cr.set_line_cap(cairo::LineCap::Square);
+    ///
+    ///     // Draw a rectangle
+    ///     cr.move_to(-1.0, -3.0);
+    ///     cr.line_to(6.0, -3.0);
+    ///     cr.line_to(6.0, 3.0);
+    ///     cr.line_to(-1.0, 3.0);
+    ///     cr.close_path();
+    ///     cr.set_line_width(0.1);
+    ///     cr.set_source_rgba(0.0, 0.0, 1.0, 1.0); // Blue line
+    ///    cr.stroke()?;
+    ///
+    ///     // Draw the origin as a black "L" shape
+    ///     cr.move_to(0.0, 0.0);
+    ///     cr.line_to(0.5, 0.0);
+    ///     cr.set_line_width(0.2);
+    ///     cr.set_source_rgba(0.0, 0.0, 0.0, 1.0); // Black line
+    ///     cr.stroke()?;
+    ///
+    ///     cr.move_to(0.0, 0.0);
+    ///     cr.line_to(0.0, 0.5);
+    ///     cr.set_line_width(0.2);
+    ///     cr.set_source_rgba(0.0, 0.0, 0.0, 1.0); // Black line
+    ///     cr.stroke()?;
+    ///
+    ///     return Ok(());
+    /// };
+    ///
+    /// let path = std::path::Path::new("docs/img/rectangle_with_origin.svg");
+    /// assert!(viewport.write_to_file(path, draw_callback).is_ok());
+    /// ```
+    #[doc = ""]
+    #[cfg_attr(
+        feature = "doc-images",
+        doc = "![Rectangle with origin][rectangle_with_origin]"
+    )]
+    #[cfg_attr(
+        feature = "doc-images",
+        embed_doc_image::embed_doc_image(
+            "rectangle_with_origin",
+            "docs/img/rectangle_with_origin.svg"
+        )
+    )]
+    #[cfg_attr(
+        not(feature = "doc-images"),
+        doc = "**Doc images not enabled**. Compile docs with `cargo doc --features 'doc-images'` and Rust version >= 1.54."
+    )]
+    pub fn write_to_file<F, P>(&self, path: P, draw_callback: F) -> Result<(), Error>
+    where
+        F: for<'a> FnOnce(&'a cairo::Context) -> Result<(), cairo::Error>,
+        P: AsRef<Path>,
+    {
+        let format = OutputFormat::from_path(&path)?;
+        return self.write_to_file_as(path, format, draw_callback);
+    }
+
+    /**
+    Draws an image with the given `draw_callback` and saves it in the given
+    [`OutputFormat`] into the file specified via `path`.
+
+    In contrast to [`Viewport::write_to_file`], the file extension of `path` is
+    ignored. This allows e.g. writing a .pdf file without a .pdf suffix.
+
+    The image is first written into a temporary file in the same directory as
+    `path`, which is then renamed to `path` once the image has been written
+    completely. Hence, an existing file at `path` is replaced atomically and
+    neither a half-written image nor stale content of the previous file is ever
+    visible. If drawing or writing fails, the temporary file is removed and an
+    existing file at `path` is left untouched.
+
+    # Examples
+
+    ```
+    use cairo_viewport::{OutputFormat, Viewport, SideLength};
+    use bounding_box::BoundingBox;
+
+    let bb = BoundingBox::new(-1.0, 1.0, -1.0, 1.0);
+    let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(100));
+
+    let path = std::env::temp_dir().join("cairo_viewport_write_to_file_as");
+    viewport.write_to_file_as(&path, OutputFormat::Pdf, |cr| {
+        cr.arc(0.0, 0.0, 0.5, 0.0, 2.0 * std::f64::consts::PI);
+        return cr.fill();
+    }).unwrap();
+    assert!(std::fs::read(&path).unwrap().starts_with(b"%PDF"));
+    std::fs::remove_file(&path).unwrap();
+    ```
+     */
+    pub fn write_to_file_as<F, P>(
+        &self,
+        path: P,
+        format: OutputFormat,
+        draw_callback: F,
+    ) -> Result<(), Error>
+    where
+        F: for<'a> FnOnce(&'a cairo::Context) -> Result<(), cairo::Error>,
+        P: AsRef<Path>,
+    {
+        return write_file_atomically(path.as_ref(), |file| {
+            return self.write_to(file, format, draw_callback);
+        });
+    }
+
+    /**
+    Like [`Viewport::write_to_file`], but with additional [`WriteOptions`] such
+    as the [`DocumentMetadata`] of a .pdf file.
+
+    # Examples
+
+    ```
+    use cairo_viewport::{DocumentMetadata, Viewport, SideLength, WriteOptions};
+    use bounding_box::BoundingBox;
+
+    let bb = BoundingBox::new(-1.0, 1.0, -1.0, 1.0);
+    let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(100));
+
+    let options = WriteOptions {
+        metadata: DocumentMetadata {
+            title: Some("Circle".into()),
+            author: Some("cairo_viewport".into()),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let path = std::env::temp_dir().join("cairo_viewport_write_to_file_with_options.pdf");
+    viewport.write_to_file_with_options(&path, &options, |cr| {
+        cr.arc(0.0, 0.0, 0.5, 0.0, 2.0 * std::f64::consts::PI);
+        return cr.fill();
+    }).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    ```
+     */
+    pub fn write_to_file_with_options<F, P>(
+        &self,
+        path: P,
+        options: &WriteOptions,
+        draw_callback: F,
+    ) -> Result<(), Error>
+    where
+        F: for<'a> FnOnce(&'a cairo::Context) -> Result<(), cairo::Error>,
+        P: AsRef<Path>,
+    {
+        let format = OutputFormat::from_path(&path)?;
+        return write_file_atomically(path.as_ref(), |file| {
+            return self.write_to_with_options(file, format, options, draw_callback);
+        });
+    }
+
+    /**
+    Draws an image with the given `draw_callback` once and saves it into all
+    files specified via `paths`. The format of each file is derived from its
+    extension, see [`Viewport::write_to_file`].
+
+    The drawing is recorded into a
+    [`RecordingSurface`](cairo::RecordingSurface) (in device coordinates of
+    this viewport), which is then replayed onto the surface of each file.
+    Hence, `draw_callback` is called only once even if expensive and vector
+    formats still receive vector graphics.
+
+    If `draw_callback` fails, no file is written and the error is returned
+    directly. Otherwise, the returned vector contains the result of writing
+    each file, in the same order as `paths`. A failure to write one file does
+    not prevent the other files from being written.
+
+    # Examples
+
+    ```
+    use cairo_viewport::{Viewport, SideLength};
+    use bounding_box::BoundingBox;
+
+    let bb = BoundingBox::new(-1.0, 1.0, -1.0, 1.0);
+    let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(100));
+
+    let dir = std::env::temp_dir();
+    let paths = [
+        dir.join("cairo_viewport_write_to_files.png"),
+        dir.join("cairo_viewport_write_to_files.svg"),
+        dir.join("cairo_viewport_write_to_files.pdf"),
+    ];
+    let results = viewport
+        .write_to_files(&paths, |cr| {
+            cr.arc(0.0, 0.0, 0.5, 0.0, 2.0 * std::f64::consts::PI);
+            return cr.fill();
+        })
+        .unwrap();
+    for (path, result) in paths.iter().zip(results) {
+        assert!(result.is_ok());
+        std::fs::remove_file(path).unwrap();
+    }
+    ```
+     */
+    pub fn write_to_files<F, P>(
+        &self,
+        paths: &[P],
+        draw_callback: F,
+    ) -> Result<Vec<Result<(), Error>>, Error>
+    where
+        F: for<'a> FnOnce(&'a cairo::Context) -> Result<(), cairo::Error>,
+        P: AsRef<Path>,
+    {
+        let recording = cairo::RecordingSurface::create(
+            cairo::Content::ColorAlpha,
+            Some(cairo::Rectangle::new(
+                0.0,
+                0.0,
+                self.width.into(),
+                self.height.into(),
+            )),
+        )?;
+        // The debug overlay is added when writing each file
+        self.draw_without_overlay(&recording, draw_callback)?;
+
+        let results = paths
+            .iter()
+            .map(|path| {
+                return self.write_to_file(path, |cr| {
+                    // The recording is already in device coordinates
+                    cr.identity_matrix();
+                    cr.set_source_surface(&recording, 0.0, 0.0)?;
+                    return cr.paint();
+                });
+            })
+            .collect();
+        return Ok(results);
+    }
+
+    /**
+    Draws an image with the given `draw_callback` and writes it in the given
+    [`OutputFormat`] into `writer`.
+
+    This allows streaming the image directly into any [`std::io::Write`]
+    implementor, e.g. a socket, a compression encoder or
+    [`std::io::stdout`]. The vector formats are created via the `for_stream`
+    constructors of the corresponding cairo surfaces and written while cairo
+    produces the output, .png images are encoded via
+    [`cairo::ImageSurface::write_to_png`] after drawing.
+
+    Since cairo may keep writing to the stream until the surface is finished,
+    `writer` needs to be owned by the surface. It is returned after the image
+    has been written completely, e.g. to finish a compression encoder. To write
+    into a borrowed writer, use [`Viewport::render_to_vec`] instead and copy the
+    bytes afterwards.
+
+    # Examples
+
+    ```
+    use cairo_viewport::{OutputFormat, Viewport, SideLength};
+    use bounding_box::BoundingBox;
+    use std::io::Cursor;
+
+    let bb = BoundingBox::new(-1.0, 1.0, -1.0, 1.0);
+    let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(100));
+
+    let cursor = viewport.write_to(Cursor::new(Vec::new()), OutputFormat::Svg, |cr| {
+        cr.arc(0.0, 0.0, 0.5, 0.0, 2.0 * std::f64::consts::PI);
+        return cr.fill();
+    }).unwrap();
+    assert!(String::from_utf8(cursor.into_inner()).unwrap().contains("<svg"));
+    ```
+     */
+    pub fn write_to<W, F>(
+        &self,
+        writer: W,
+        format: OutputFormat,
+        draw_callback: F,
+    ) -> Result<W, Error>
+    where
+        W: Write + 'static,
+        F: for<'a> FnOnce(&'a cairo::Context) -> Result<(), cairo::Error>,
+    {
+        return self.write_to_with_options(writer, format, &WriteOptions::default(), draw_callback);
+    }
+
+    /**
+    Like [`Viewport::write_to`], but with additional [`WriteOptions`] such as
+    the [`DocumentMetadata`] of a .pdf file.
+     */
+    pub fn write_to_with_options<W, F>(
+        &self,
+        writer: W,
+        format: OutputFormat,
+        options: &WriteOptions,
+        draw_callback: F,
+    ) -> Result<W, Error>
+    where
+        W: Write + 'static,
+        F: for<'a> FnOnce(&'a cairo::Context) -> Result<(), cairo::Error>,
+    {
+        let width: f64 = self.width.into();
+        let height: f64 = self.height.into();
+        let surface: cairo::Surface = match format {
+            OutputFormat::Png => {
+                let surface = options
+                    .png
+                    .convert_surface(self.render_to_image_surface(draw_callback)?)?;
+                let mut writer = writer;
+                if options.png.text.is_empty() {
+                    surface.write_to_png(&mut writer)?;
+                } else {
+                    let mut png = Vec::new();
+                    surface.write_to_png(&mut png)?;
+                    writer.write_all(&options.png.insert_text_chunks(png)?)?;
+                }
+                return Ok(writer);
+            }
+            OutputFormat::Pdf => {
+                let surface = cairo::PdfSurface::for_stream(width, height, writer)?;
+                options.metadata.apply(&surface)?;
+                (*surface).clone()
+            }
+            OutputFormat::Ps => {
+                let surface = cairo::PsSurface::for_stream(width, height, writer)?;
+                if let Some(level) = options.ps_level {
+                    surface.restrict(level);
+                }
+                (*surface).clone()
+            }
+            OutputFormat::Eps => {
+                let surface = cairo::PsSurface::for_stream(width, height, Vec::new())?;
+                surface.set_eps(true);
+                if let Some(level) = options.ps_level {
+                    surface.restrict(level);
+                }
+                let eps = self.draw_and_finish(&surface, draw_callback)?;
+                let mut writer = writer;
+                writer.write_all(&self.set_eps_bounding_box(eps))?;
+                return Ok(writer);
+            }
+            OutputFormat::Svg if options.svg.world_view_box => {
+                let mut options = options.clone();
+                options.svg.world_view_box = false;
+                let svg =
+                    self.write_to_with_options(Vec::new(), format, &options, draw_callback)?;
+                let mut writer = writer;
+                writer.write_all(&self.set_world_view_box(svg)?)?;
+                return Ok(writer);
+            }
+            #[cfg(feature = "image")]
+            OutputFormat::Jpeg
+            | OutputFormat::Webp
+            | OutputFormat::Tiff
+            | OutputFormat::Bmp
+            | OutputFormat::Gif => {
+                let bytes = self.encode_with_image_crate(format, &options.raster, draw_callback)?;
+                let mut writer = writer;
+                writer.write_all(&bytes)?;
+                return Ok(writer);
+            }
+            OutputFormat::Svg => {
+                let mut surface = cairo::SvgSurface::for_stream(width, height, writer)?;
+                if let Some(unit) = options.svg.unit {
+                    surface.set_document_unit(unit);
+                }
+                if let Some(version) = options.svg.version {
+                    surface.restrict(version);
+                }
+                (*surface).clone()
+            }
+        };
+
+        return self.draw_and_finish(&surface, draw_callback);
+    }
+
+    /**
+    Draws an image with the given `draw_callback` and returns the encoded image
+    as bytes.
+
+    This is the in-memory counterpart to [`Viewport::write_to_file`], which is
+    useful e.g. for serving images from a web backend without touching the file
+    system. It is a shorthand for [`Viewport::write_to`] with an empty
+    [`Vec`] as writer.
+
+    # Examples
+
+    ```
+    use cairo_viewport::{OutputFormat, Viewport, SideLength};
+    use bounding_box::BoundingBox;
+
+    let bb = BoundingBox::new(-1.0, 1.0, -1.0, 1.0);
+    let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(100));
+
+    let draw_callback = |cr: &cairo::Context| {
+        cr.arc(0.0, 0.0, 0.5, 0.0, 2.0 * std::f64::consts::PI);
+        return cr.fill();
+    };
+
+    let png = viewport.render_to_vec(OutputFormat::Png, draw_callback).unwrap();
+    assert!(png.starts_with(b"\x89PNG"));
+
+    let pdf = viewport.render_to_vec(OutputFormat::Pdf, draw_callback).unwrap();
+    assert!(pdf.starts_with(b"%PDF"));
+
+    let ps = viewport.render_to_vec(OutputFormat::Ps, draw_callback).unwrap();
+    assert!(ps.starts_with(b"%!PS"));
+
+    let svg = viewport.render_to_vec(OutputFormat::Svg, draw_callback).unwrap();
+    assert!(String::from_utf8(svg).unwrap().contains("<svg"));
+    ```
+     */
+    pub fn render_to_vec<F>(&self, format: OutputFormat, draw_callback: F) -> Result<Vec<u8>, Error>
+    where
+        F: for<'a> FnOnce(&'a cairo::Context) -> Result<(), cairo::Error>,
+    {
+        return self.write_to(Vec::new(), format, draw_callback);
+    }
+
+    /**
+    Draws an image with the given `draw_callback` onto a new
+    [`ImageSurface`](cairo::ImageSurface) and returns the surface.
+
+    The surface has the size of `self` and uses the
+    [`ARgb32`](cairo::Format::ARgb32) format, i.e. the pixels are stored as
+    native-endian `u32` values with premultiplied alpha. This is useful for
+    uploading the image e.g. as a texture or for post-processing the pixels
+    without encoding them first. See [`Viewport::render_to_rgba_image`] for a
+    conversion into straight (non-premultiplied) RGBA.
+
+    # Examples
+
+    ```
+    use cairo_viewport::{Viewport, SideLength};
+    use bounding_box::BoundingBox;
+
+    let bb = BoundingBox::new(-1.0, 1.0, -1.0, 1.0);
+    let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(100));
+
+    let surface = viewport.render_to_image_surface(|cr| {
+        cr.set_source_rgb(1.0, 0.0, 0.0);
+        return cr.paint();
+    }).unwrap();
+    assert_eq!(surface.width(), 100);
+    assert_eq!(surface.height(), 100);
+    assert_eq!(surface.format(), cairo::Format::ARgb32);
+    ```
+     */
+    pub fn render_to_image_surface<F>(&self, draw_callback: F) -> Result<cairo::ImageSurface, Error>
+    where
+        F: for<'a> FnOnce(&'a cairo::Context) -> Result<(), cairo::Error>,
+    {
+        let surface = cairo::ImageSurface::create(
+            cairo::Format::ARgb32,
+            self.width as i32,
+            self.height as i32,
+        )?;
+        self.draw_on_surface(&surface, draw_callback)?;
+        surface.flush();
+        return Ok(surface);
+    }
+
+    /**
+    Draws an image with the given `draw_callback` and returns it as an
+    [`RgbaImage`](image::RgbaImage).
+
+    cairo stores pixels with premultiplied alpha, while [`image::RgbaImage`]
+    expects straight alpha. The color channels are therefore divided by the
+    alpha value of each pixel (fully transparent pixels become
+    `[0, 0, 0, 0]`).
+
+    Only available if the `image` feature is enabled.
+
+    # Examples
+
+    ```
+    use cairo_viewport::{Viewport, SideLength};
+    use bounding_box::BoundingBox;
+
+    let bb = BoundingBox::new(-1.0, 1.0, -1.0, 1.0);
+    let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(10));
+
+    let image = viewport.render_to_rgba_image(|cr| {
+        cr.set_source_rgba(1.0, 0.0, 0.0, 0.5);
+        return cr.paint();
+    }).unwrap();
+    assert_eq!(image.dimensions(), (10, 10));
+    assert_eq!(image.get_pixel(5, 5).0, [255, 0, 0, 128]);
+    ```
+     */
+    #[cfg(feature = "image")]
+    pub fn render_to_rgba_image<F>(&self, draw_callback: F) -> Result<image::RgbaImage, Error>
+    where
+        F: for<'a> FnOnce(&'a cairo::Context) -> Result<(), cairo::Error>,
+    {
+        return rgba_image_from_surface(&self.render_to_image_surface(draw_callback)?);
+    }
+
+    /**
+    Draws an image with the given `draw_callback` and encodes it in one of the
+    raster formats which are not supported by cairo itself (e.g.
+    [`OutputFormat::Jpeg`]) via the [image] crate.
+     */
+    #[cfg(feature = "image")]
+    fn encode_with_image_crate<F>(
+        &self,
+        format: OutputFormat,
+        options: &RasterOptions,
+        draw_callback: F,
+    ) -> Result<Vec<u8>, Error>
+    where
+        F: for<'a> FnOnce(&'a cairo::Context) -> Result<(), cairo::Error>,
+    {
+        let mut surface = self.render_to_image_surface(draw_callback)?;
+
+        // JPEG does not support transparency, dark drawings need a bright background
+        let background = match format {
+            OutputFormat::Jpeg => Some(options.background.unwrap_or([1.0, 1.0, 1.0])),
+            _ => options.background,
+        };
+        if background.is_some() {
+            let png = PngOptions {
+                background,
+                ..Default::default()
+            };
+            surface = png.convert_surface(surface)?;
+        }
+        let image = image::DynamicImage::ImageRgba8(rgba_image_from_surface(&surface)?);
+
+        let mut bytes = Vec::new();
+        let image_format = match format {
+            OutputFormat::Jpeg => {
+                if let Some(quality) = options.jpeg_quality {
+                    image.write_with_encoder(
+                        image::codecs::jpeg::JpegEncoder::new_with_quality(&mut bytes, quality),
+                    )?;
+                    return Ok(bytes);
+                }
+                image::ImageFormat::Jpeg
+            }
+            OutputFormat::Webp => image::ImageFormat::WebP,
+            OutputFormat::Tiff => image::ImageFormat::Tiff,
+            OutputFormat::Bmp => image::ImageFormat::Bmp,
+            OutputFormat::Gif => image::ImageFormat::Gif,
+            _ => unreachable!("{format:?} is written by cairo"),
+        };
+        image.write_to(std::io::Cursor::new(&mut bytes), image_format)?;
+        return Ok(bytes);
+    }
+
+    /**
+    Creates a [`cairo::Context`] for `surface`, applies `self` to it and calls
+    `draw_callback`. Afterwards, the [`Viewport::debug_overlay`] is drawn (if
+    set).
+     */
+    fn draw_on_surface<F>(&self, surface: &cairo::Surface, draw_callback: F) -> Result<(), Error>
+    where
+        F: for<'a> FnOnce(&'a cairo::Context) -> Result<(), cairo::Error>,
+    {
+        let cr = self.draw_without_overlay(surface, draw_callback)?;
+        if let Some(overlay) = &self.debug_overlay {
+            cr.identity_matrix();
+            cr.reset_clip();
+            overlay.draw(self, &cr)?;
+        }
+        return Ok(());
+    }
+
+    /**
+    Like [`Viewport::draw_on_surface`], but without the
+    [`Viewport::debug_overlay`]. Returns the context used for drawing.
+     */
+    fn draw_without_overlay<F>(
+        &self,
+        surface: &cairo::Surface,
+        draw_callback: F,
+    ) -> Result<cairo::Context, Error>
+    where
+        F: for<'a> FnOnce(&'a cairo::Context) -> Result<(), cairo::Error>,
+    {
+        let cr = cairo::Context::new(surface)?;
+        self.apply(&cr);
+        draw_callback(&cr)?;
+        return Ok(cr);
+    }
+
+    /**
+    Draws onto `surface` (which was created with a stream of type `W`) via
+    [`Viewport::draw_on_surface`], finishes it and returns the stream.
+     */
+    fn draw_and_finish<W, F>(&self, surface: &cairo::Surface, draw_callback: F) -> Result<W, Error>
+    where
+        W: 'static,
+        F: for<'a> FnOnce(&'a cairo::Context) -> Result<(), cairo::Error>,
+    {
+        // Finish the surface even if drawing failed, so that the stream is released.
+        let result = self.draw_on_surface(surface, draw_callback);
+        let stream = surface.finish_output_stream()?;
+        result?;
+        return Ok(*stream
+            .downcast::<W>()
+            .expect("surface was created with a stream of type W"));
+    }
+
+    /**
+    Replaces the bounding box comments of `eps` (created by cairo) by the size
+    of the image.
+
+    cairo derives the bounding box of an EPS file from the ink extents of the
+    drawing. However, the image is meant to have the size of the viewport, just
+    like all other output formats.
+     */
+    fn set_eps_bounding_box(&self, eps: Vec<u8>) -> Vec<u8> {
+        let bounding_box = format!("0 0 {} {}", self.width, self.height);
+        let mut output = Vec::with_capacity(eps.len());
+        for line in eps.split_inclusive(|byte| *byte == b'\n') {
+            let comment = [
+                "%%BoundingBox:",
+                "%%HiResBoundingBox:",
+                "%%PageBoundingBox:",
+            ]
+            .into_iter()
+            .find(|comment| line.starts_with(comment.as_bytes()));
+            match comment {
+                Some(comment) => {
+                    output.extend_from_slice(format!("{comment} {bounding_box}\n").as_bytes())
+                }
+                None => output.extend_from_slice(line),
+            }
+        }
+        return output;
+    }
+
+    /**
+    Replaces the `viewBox` of the root element of `svg` (created by cairo) by
+    [`Viewport::visible_world_bounds`] and wraps its content in a group which
+    transforms world coordinates back into image coordinates, see
+    [`SvgOptions::world_view_box`].
+     */
+    fn set_world_view_box(&self, svg: Vec<u8>) -> Result<Vec<u8>, Error> {
+        if self.rotation != 0.0 {
+            return Err(Error::RotatedWorldViewBox(self.rotation));
+        }
+
+        // Maps image coordinates to world coordinates (with negated y-values for YAxis::Up)
+        let mut transform = self.matrix().try_invert()?;
+        let bounds = self.visible_world_bounds();
+        let (ymin, ymax) = match self.y_axis {
+            YAxis::Down => (bounds.ymin(), bounds.ymax()),
+            YAxis::Up => {
+                transform = cairo::Matrix::multiply(
+                    &transform,
+                    &cairo::Matrix::new(1.0, 0.0, 0.0, -1.0, 0.0, 0.0),
+                );
+                (-bounds.ymax(), -bounds.ymin())
+            }
+        };
+
+        let malformed = |reason: &str| Error::MalformedSvg(reason.to_owned());
+        let svg = String::from_utf8(svg).map_err(|_| malformed("file is not UTF-8 encoded"))?;
+        let root_start = svg
+            .find("<svg")
+            .ok_or_else(|| malformed("root element is missing"))?;
+        let root_end = root_start
+            + svg[root_start..]
+                .find('>')
+                .ok_or_else(|| malformed("root element is not closed"))?;
+        let content_end = svg
+            .rfind("</svg>")
+            .filter(|content_end| *content_end > root_end)
+            .ok_or_else(|| malformed("closing tag of the root element is missing"))?;
+
+        // Remove the viewBox created by cairo from the attributes of the root element
+        let mut root = svg[root_start..root_end].to_owned();
+        if let Some(start) = root.find(" viewBox=\"") {
+            let value_start = start + " viewBox=\"".len();
+            let end = value_start
+                + root[value_start..]
+                    .find('"')
+                    .ok_or_else(|| malformed("viewBox attribute is not closed"))?;
+            root.replace_range(start..=end, "");
+        }
+
+        let mut output = String::with_capacity(svg.len() + 200);
+        output.push_str(&svg[..root_start]);
+        output.push_str(&root);
+        output.push_str(&format!(
+            " viewBox=\"{} {} {} {}\" preserveAspectRatio=\"none\">\n<g transform=\"matrix({},{},{},{},{},{})\">",
+            bounds.xmin(),
+            ymin,
+            bounds.width(),
+            ymax - ymin,
+            transform.xx(),
+            transform.yx(),
+            transform.xy(),
+            transform.yy(),
+            transform.x0(),
+            transform.y0(),
+        ));
+        output.push_str(&svg[root_end + 1..content_end]);
+        output.push_str("</g>\n");
+        output.push_str(&svg[content_end..]);
+        return Ok(output.into_bytes());
+    }
+
+    /**
+    A wrapper around [`compare_to_image`] which uses [`Viewport::write_to_file`]
+    as the `draw_callback`.
+
+    Only available if the `image-compare` feature is enabled.
+
+    # Examples
+
+    ```
+    use cairo_viewport::{compare_to_image, SideLength, Viewport};
+    use bounding_box::BoundingBox;
+    use std::path::Path;
+
+    let bb = BoundingBox::new(-1.0, 1.0, -1.0, 1.0);
+    let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(500));
+
+    fn draw_cross(cr: &cairo::Context, color: [f64; 4]) -> Result<(), cairo::Error> {
+
+        // Set the background to white
+        cr.set_source_rgb(1.0, 1.0, 1.0);
+        cr.paint()?;
+
+        cr.move_to(-1.0, 0.0);
+        cr.line_to(1.0, 0.0);
+        cr.set_line_width(0.2);
+        cr.set_source_rgba(color[0], color[1], color[2], color[3]);
+        cr.stroke()?;
+
+        cr.move_to(0.0, -1.0);
+        cr.line_to(0.0, 1.0);
+        cr.set_line_width(0.2);
+        cr.set_source_rgba(color[0], color[1], color[2], color[3]);
+        cr.stroke()?;
 
         return Ok(());
     }
 
     let path = "tests/img/black_cross_compare_to_image_method.png";
 
-    // Creates the comparison image
-    viewport
-        .write_to_file(path, |cr| draw_cross(cr, [0.0, 0.0, 0.0, 1.0]))
-        .unwrap();
+    // Creates the comparison image
+    viewport
+        .write_to_file(path, |cr| draw_cross(cr, [0.0, 0.0, 0.0, 1.0]))
+        .unwrap();
+
+    // Compare to a second black cross
+    viewport.compare_to_image(path, |cr: &cairo::Context| {
+        draw_cross(cr, [0.0, 0.0, 0.0, 1.0])
+    }, 0.99)
+    .expect("images should be identical");
+
+    // Draw a blue cross
+    let err = viewport.compare_to_image(path, |cr: &cairo::Context| {
+        draw_cross(cr, [0.0, 0.0, 1.0, 1.0])
+    }, 0.99)
+    .unwrap_err();
+    match err {
+        cairo_viewport::Error::ImageCompFailed {
+            reference_image: _,
+            image_created_from_fn,
+        } => {
+            let _ = std::fs::remove_file(&image_created_from_fn).unwrap();
+        }
+        _ => panic!("{err}"),
+    }
+    ```
+    */
+    #[cfg(feature = "image-compare")]
+    pub fn compare_to_image<F, P>(
+        &self,
+        image: P,
+        draw_callback: F,
+        required_relative_similarity: f64,
+    ) -> Result<(), Error>
+    where
+        F: for<'a> FnOnce(&'a cairo::Context) -> Result<(), cairo::Error>,
+        P: AsRef<Path>,
+    {
+        return compare_to_image(
+            image,
+            |path: &Path| self.write_to_file(path, draw_callback),
+            required_relative_similarity,
+        );
+    }
+
+    /**
+    A wrapper around [`compare_to_image`] which uses [`Viewport::write_to_file`]
+    as the `draw_callback`.
+
+    Only available if the `image-compare` feature is enabled.
+
+    # Examples
+
+    ```
+    use cairo_viewport::{compare_or_create, SideLength, Viewport};
+    use bounding_box::BoundingBox;
+    use std::path::Path;
+
+    let bb = BoundingBox::new(-1.0, 1.0, -1.0, 1.0);
+    let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(500));
+
+    fn draw_cross(cr: &cairo::Context, color: [f64; 4]) -> Result<(), cairo::Error> {
+
+        // Set the background to white
+        cr.set_source_rgb(1.0, 1.0, 1.0);
+        cr.paint()?;
+
+        cr.move_to(-1.0, 0.0);
+        cr.line_to(1.0, 0.0);
+        cr.set_line_width(0.2);
+        cr.set_source_rgba(color[0], color[1], color[2], color[3]);
+        cr.stroke()?;
+
+        cr.move_to(0.0, -1.0);
+        cr.line_to(0.0, 1.0);
+        cr.set_line_width(0.2);
+        cr.set_source_rgba(color[0], color[1], color[2], color[3]);
+        cr.stroke()?;
+
+        return Ok(());
+    }
+
+    let path = Path::new("tests/img/black_cross_compare_or_create_method.png");
+
+    assert!(!path.exists());
+
+    // Creates the reference image
+    viewport.compare_or_create(path, |cr| draw_cross(cr, [0.0, 0.0, 0.0, 1.0]), 0.99).expect("image is created");
+
+    assert!(path.exists());
 
-    // Compare to a second black cross
-    viewport.compare_to_image(path, |cr: &cairo::Context| {
-        draw_cross(cr, [0.0, 0.0, 0.0, 1.0])
-    }, 0.99)
-    .expect("images should be identical");
+    // Reference image exists -> compare
+    viewport.compare_or_create(path, |cr| draw_cross(cr, [0.0, 0.0, 0.0, 1.0]), 0.99).expect("comparison succeeded");
 
     // Draw a blue cross
-    let err = viewport.compare_to_image(path, |cr: &cairo::Context| {
-        draw_cross(cr, [0.0, 0.0, 1.0, 1.0])
-    }, 0.99)
-    .unwrap_err();
+    let err = viewport.compare_or_create(path, |cr| draw_cross(cr, [0.0, 0.0, 1.0, 1.0]), 0.99).unwrap_err();
     match err {
         cairo_viewport::Error::ImageCompFailed {
             reference_image: _,
@@ -390,10 +2763,13 @@ impl Viewport {
         }
         _ => panic!("{err}"),
     }
+
+    // Remove the reference image
+    std::fs::remove_file(&path).unwrap();
     ```
     */
     #[cfg(feature = "image-compare")]
-    pub fn compare_to_image<F, P>(
+    pub fn compare_or_create<F, P>(
         &self,
         image: P,
         draw_callback: F,
@@ -403,95 +2779,1017 @@ impl Viewport {
         F: for<'a> FnOnce(&'a cairo::Context) -> Result<(), cairo::Error>,
         P: AsRef<Path>,
     {
-        return compare_to_image(
+        return compare_or_create(
             image,
             |path: &Path| self.write_to_file(path, draw_callback),
             required_relative_similarity,
         );
     }
+}
+
+/**
+A drawing recorded in drawing ("world") coordinates, see
+[`Viewport::fit_to_drawing`].
+
+The drawing is recorded into a [`RecordingSurface`](cairo::RecordingSurface)
+and can be replayed any number of times onto other contexts, e.g. within the
+callback of [`Viewport::write_to_file`]. When replayed onto vector surfaces
+(.pdf, .svg, ...), the drawing stays a vector graphic.
+
+cairo measures the ink extents of a recording in whole surface units and
+stores path coordinates as fixed-point numbers with 24 integer bits and 8
+fractional bits. Therefore, the world coordinates are multiplied by a
+`resolution` factor while recording: The ink extents are accurate to
+`1 / resolution` world units, while all world coordinates need to stay within
+`±2^23 / resolution`. The default resolution used by [`RecordedDrawing::new`]
+is [`RecordedDrawing::DEFAULT_RESOLUTION`]; drawings with very large or very
+small world coordinates should use [`RecordedDrawing::with_resolution`].
+ */
+#[derive(Debug, Clone)]
+pub struct RecordedDrawing {
+    surface: cairo::RecordingSurface,
+    resolution: f64,
+}
+
+impl RecordedDrawing {
+    /// Resolution (recording units per world unit) used by [`RecordedDrawing::new`].
+    pub const DEFAULT_RESOLUTION: f64 = 256.0;
 
     /**
-    A wrapper around [`compare_to_image`] which uses [`Viewport::write_to_file`]
-    as the `draw_callback`.
+    Records `draw_callback` into a new, unbounded
+    [`RecordingSurface`](cairo::RecordingSurface) using
+    [`RecordedDrawing::DEFAULT_RESOLUTION`].
+     */
+    pub fn new<F>(draw_callback: F) -> Result<Self, Error>
+    where
+        F: for<'a> FnOnce(&'a cairo::Context) -> Result<(), cairo::Error>,
+    {
+        return Self::with_resolution(Self::DEFAULT_RESOLUTION, draw_callback);
+    }
 
-    Only available if the `image-compare` feature is enabled.
+    /**
+    Records `draw_callback` into a new, unbounded
+    [`RecordingSurface`](cairo::RecordingSurface), using `resolution` recording
+    units per world unit (see the type documentation).
+
+    # Panics
+
+    Panics if `resolution` is not finite and positive.
+     */
+    pub fn with_resolution<F>(resolution: f64, draw_callback: F) -> Result<Self, Error>
+    where
+        F: for<'a> FnOnce(&'a cairo::Context) -> Result<(), cairo::Error>,
+    {
+        if !(resolution.is_finite() && resolution > 0.0) {
+            panic!("resolution must be finite and positive!")
+        }
+        let surface = cairo::RecordingSurface::create(cairo::Content::ColorAlpha, None)?;
+        let cr = cairo::Context::new(&surface)?;
+        cr.scale(resolution, resolution);
+        draw_callback(&cr)?;
+        return Ok(RecordedDrawing {
+            surface,
+            resolution,
+        });
+    }
+
+    /// Returns the resolution (recording units per world unit) of the drawing.
+    pub fn resolution(&self) -> f64 {
+        return self.resolution;
+    }
+
+    /**
+    Returns the [`BoundingBox`] (in world coordinates) of the area which has
+    actually been painted, including e.g. the width of strokes. The bounding
+    box is rounded outwards to multiples of `1 / resolution`. If nothing has
+    been painted, it has zero width and height.
+
+    Returns [`Error::UnboundedDrawing`] if the painted area is unbounded, e.g.
+    because a background was filled via [`cairo::Context::paint`] without a
+    clip. Fill a rectangle instead to give the background a finite size.
+
+    Note that cairo estimates the extents of strokes conservatively: Curved
+    paths stroked with [`LineJoin::Miter`](cairo::LineJoin::Miter) (the
+    default) are padded by the maximum possible miter length. Use a round or
+    bevel line join for such paths to get tight extents.
+     */
+    pub fn ink_extents(&self) -> Result<BoundingBox, Error> {
+        let ink = bounded_ink_extents(&self.surface)?;
+        return Ok(BoundingBox::new(
+            ink.xmin() / self.resolution,
+            ink.xmax() / self.resolution,
+            ink.ymin() / self.resolution,
+            ink.ymax() / self.resolution,
+        ));
+    }
+
+    /**
+    Paints the recorded drawing onto `cr`, using its current transformation
+    matrix to map world coordinates. Hence, the drawing is placed correctly
+    when replayed inside the callback of e.g. [`Viewport::write_to_file`] or
+    [`Viewport::with_context`].
+     */
+    pub fn replay(&self, cr: &cairo::Context) -> Result<(), cairo::Error> {
+        cr.save()?;
+        cr.scale(1.0 / self.resolution, 1.0 / self.resolution);
+        let result = cr
+            .set_source_surface(&self.surface, 0.0, 0.0)
+            .and_then(|_| cr.paint());
+        cr.restore()?;
+        return result;
+    }
+
+    /// Returns the underlying [`RecordingSurface`](cairo::RecordingSurface).
+    pub fn surface(&self) -> &cairo::RecordingSurface {
+        return &self.surface;
+    }
+}
+
+/**
+A multi-page PDF or PostScript document where each page is configured by its
+own [`Viewport`].
+
+[`Viewport::write_to_file`] creates exactly one page per file. A
+[`ViewportDocument`] instead collects any number of pages, each with the size
+of the [`Viewport`] given to [`ViewportDocument::add_page`]. The file type is
+derived from the extension of the path given to [`ViewportDocument::create`]
+(.pdf or .ps).
+
+Like [`Viewport::write_to_file`], the document is written into a temporary file
+which only replaces the target file when [`ViewportDocument::finish`] succeeds.
+Dropping the document without calling [`ViewportDocument::finish`] discards it.
+
+# Examples
+
+```
+use cairo_viewport::{SideLength, Viewport, ViewportDocument};
+use bounding_box::BoundingBox;
+
+let path = std::env::temp_dir().join("cairo_viewport_document_example.pdf");
+let mut document = ViewportDocument::create(&path).unwrap();
+
+let circle = Viewport::from_bounding_box(&BoundingBox::new(-1.0, 1.0, -1.0, 1.0), SideLength::Long(200));
+document.add_page(&circle, |cr| {
+    cr.arc(0.0, 0.0, 1.0, 0.0, 2.0 * std::f64::consts::PI);
+    return cr.fill();
+}).unwrap();
+
+let rectangle = Viewport::from_bounding_box(&BoundingBox::new(0.0, 3.0, 0.0, 1.0), SideLength::Long(300));
+document.add_page(&rectangle, |cr| {
+    cr.rectangle(0.5, 0.25, 2.0, 0.5);
+    return cr.fill();
+}).unwrap();
+
+assert_eq!(document.page_count(), 2);
+document.finish().unwrap();
+assert!(std::fs::read(&path).unwrap().starts_with(b"%PDF"));
+# std::fs::remove_file(&path).unwrap();
+```
+ */
+pub struct ViewportDocument {
+    surface: Option<DocumentSurface>,
+    path: std::path::PathBuf,
+    tmp_path: std::path::PathBuf,
+    page_count: usize,
+}
+
+/**
+The cairo surfaces which support multiple pages.
+ */
+enum DocumentSurface {
+    Pdf(cairo::PdfSurface),
+    Ps(cairo::PsSurface),
+}
+
+impl DocumentSurface {
+    fn surface(&self) -> &cairo::Surface {
+        return match self {
+            DocumentSurface::Pdf(surface) => surface,
+            DocumentSurface::Ps(surface) => surface,
+        };
+    }
+}
+
+impl ViewportDocument {
+    /**
+    Creates a new document which will be written to `path` by
+    [`ViewportDocument::finish`].
+
+    The format is derived from the file extension of `path`. Only .pdf and .ps
+    support multiple pages, all other formats return an
+    [`Error::UnsupportedDocumentFormat`].
+     */
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let format = OutputFormat::from_path(path)?;
+        if format != OutputFormat::Pdf && format != OutputFormat::Ps {
+            return Err(Error::UnsupportedDocumentFormat(format));
+        }
+
+        let (tmp_path, tmp_file) = create_temporary_file(path)?;
+
+        // The size of the surface is overwritten by every page anyway.
+        let surface = match format {
+            OutputFormat::Pdf => {
+                cairo::PdfSurface::for_stream(1.0, 1.0, tmp_file).map(DocumentSurface::Pdf)
+            }
+            _ => cairo::PsSurface::for_stream(1.0, 1.0, tmp_file).map(DocumentSurface::Ps),
+        };
+        let surface = match surface {
+            Ok(surface) => surface,
+            Err(error) => {
+                let _ = std::fs::remove_file(&tmp_path);
+                return Err(error.into());
+            }
+        };
+
+        return Ok(ViewportDocument {
+            surface: Some(surface),
+            path: path.to_owned(),
+            tmp_path,
+            page_count: 0,
+        });
+    }
+
+    /**
+    Adds a page with the size of `viewport` to the document and draws its
+    content with `draw_callback`.
+
+    The callback is drawn into a [`RecordingSurface`](cairo::RecordingSurface)
+    first and only replayed onto the document if it succeeded. Therefore, a
+    failing `draw_callback` does not leave a partially drawn page behind and
+    further pages can still be added.
+     */
+    pub fn add_page<F>(&mut self, viewport: &Viewport, draw_callback: F) -> Result<(), Error>
+    where
+        F: for<'a> FnOnce(&'a cairo::Context) -> Result<(), cairo::Error>,
+    {
+        let width: f64 = viewport.width.into();
+        let height: f64 = viewport.height.into();
+
+        let recording = cairo::RecordingSurface::create(
+            cairo::Content::ColorAlpha,
+            Some(cairo::Rectangle::new(0.0, 0.0, width, height)),
+        )?;
+        viewport.draw_on_surface(&recording, draw_callback)?;
+
+        let surface = self.document_surface();
+        match surface {
+            DocumentSurface::Pdf(surface) => surface.set_size(width, height)?,
+            DocumentSurface::Ps(surface) => surface.set_size(width, height),
+        }
+        let cr = cairo::Context::new(surface.surface())?;
+        cr.set_source_surface(&recording, 0.0, 0.0)?;
+        cr.paint()?;
+        cr.show_page()?;
+        self.page_count += 1;
+        return Ok(());
+    }
+
+    /**
+    Returns the number of pages added so far.
+     */
+    pub fn page_count(&self) -> usize {
+        return self.page_count;
+    }
+
+    /**
+    Sets the [`DocumentMetadata`] of the document. Only supported by .pdf
+    documents, .ps documents ignore it.
+     */
+    pub fn set_metadata(&mut self, metadata: &DocumentMetadata) -> Result<(), Error> {
+        if let DocumentSurface::Pdf(surface) = self.document_surface() {
+            metadata.apply(surface)?;
+        }
+        return Ok(());
+    }
+
+    /**
+    Adds an outline ("bookmark") entry named `name` which points to the page
+    with the zero-based index `page`.
+
+    The entry is placed below the entry with the ID `parent`, use
+    [`cairo::PDF_OUTLINE_ROOT`] for a top-level entry. The returned ID can be
+    used as `parent` of further entries. The page does not need to exist yet,
+    but has to be added before [`ViewportDocument::finish`] is called. Only
+    supported by .pdf documents, .ps documents ignore the entry and return
+    [`cairo::PDF_OUTLINE_ROOT`].
 
     # Examples
 
     ```
-    use cairo_viewport::{compare_or_create, SideLength, Viewport};
+    use cairo_viewport::{SideLength, Viewport, ViewportDocument};
     use bounding_box::BoundingBox;
-    use std::path::Path;
 
-    let bb = BoundingBox::new(-1.0, 1.0, -1.0, 1.0);
-    let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(500));
+    let path = std::env::temp_dir().join("cairo_viewport_outline_example.pdf");
+    let mut document = ViewportDocument::create(&path).unwrap();
+    let viewport = Viewport::from_bounding_box(&BoundingBox::new(0.0, 1.0, 0.0, 1.0), SideLength::Long(100));
 
-    fn draw_cross(cr: &cairo::Context, color: [f64; 4]) -> Result<(), cairo::Error> {
+    let chapter = document
+        .add_outline(cairo::PDF_OUTLINE_ROOT, "Chapter 1", 0, cairo::PdfOutline::OPEN)
+        .unwrap();
+    for page in 0..2 {
+        document.add_page(&viewport, |cr| cr.paint()).unwrap();
+        document
+            .add_outline(chapter, &format!("Figure {}", page + 1), page, cairo::PdfOutline::empty())
+            .unwrap();
+    }
+    document.finish().unwrap();
+    # std::fs::remove_file(&path).unwrap();
+    ```
+     */
+    pub fn add_outline(
+        &mut self,
+        parent: i32,
+        name: &str,
+        page: usize,
+        flags: cairo::PdfOutline,
+    ) -> Result<i32, Error> {
+        return match self.document_surface() {
+            DocumentSurface::Pdf(surface) => {
+                Ok(surface.add_outline(parent, name, &format!("page={}", page + 1), flags)?)
+            }
+            DocumentSurface::Ps(_) => Ok(cairo::PDF_OUTLINE_ROOT),
+        };
+    }
 
-        // Set the background to white
-        cr.set_source_rgb(1.0, 1.0, 1.0);
+    fn document_surface(&self) -> &DocumentSurface {
+        return self
+            .surface
+            .as_ref()
+            .expect("surface is only taken by ViewportDocument::finish");
+    }
+
+    /**
+    Finishes the document and moves it to the path given to
+    [`ViewportDocument::create`].
+
+    Errors which occurred while writing the pages into the underlying file are
+    reported here. In that case, the target file is left untouched.
+     */
+    pub fn finish(mut self) -> Result<(), Error> {
+        let surface = self
+            .surface
+            .take()
+            .expect("surface is only taken by ViewportDocument::finish");
+        let result = surface
+            .surface()
+            .finish_output_stream()
+            .map_err(Error::from)
+            .and_then(|stream| {
+                let file = stream
+                    .downcast::<std::fs::File>()
+                    .expect("surface was created with a file stream");
+                return persist_temporary_file(&self.tmp_path, *file, &self.path);
+            });
+        if result.is_err() {
+            let _ = std::fs::remove_file(&self.tmp_path);
+        }
+        return result;
+    }
+}
+
+impl Drop for ViewportDocument {
+    fn drop(&mut self) {
+        // The document was not finished, hence discard the temporary file.
+        if let Some(surface) = self.surface.take() {
+            surface.surface().finish();
+            drop(surface);
+            let _ = std::fs::remove_file(&self.tmp_path);
+        }
+    }
+}
+
+/**
+Debug overlay which shows the world frame of a drawing, see
+[`Viewport::with_debug_overlay`].
+
+The overlay consists of a grid along the world axes with labeled coordinates,
+a crosshair at the origin of the world coordinates (red along the x-axis, green
+along the y-axis) and optionally the outline of a [`BoundingBox`] (orange),
+e.g. the one the [`Viewport`] was created from. It is drawn after the draw
+callback. All line widths and font sizes are given in device units, hence
+they do not depend on the scale of the viewport.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DebugOverlay {
+    /// Bounding box (in world coordinates) whose outline is drawn.
+    pub bounding_box: Option<BoundingBox>,
+    /**
+    Distance of the grid lines in world units. If `None`, the distance is
+    chosen separately for both axes as the smallest multiple of 1, 2 or 5
+    times a power of ten which results in grid lines at least 50 device units
+    apart.
+     */
+    pub grid_spacing: Option<f64>,
+    /// Line width in device units. Defaults to 1.0.
+    pub line_width: f64,
+    /// Font size of the coordinate labels in device units. Defaults to 10.0.
+    pub font_size: f64,
+}
+
+impl Default for DebugOverlay {
+    fn default() -> Self {
+        return DebugOverlay {
+            bounding_box: None,
+            grid_spacing: None,
+            line_width: 1.0,
+            font_size: 10.0,
+        };
+    }
+}
+
+impl DebugOverlay {
+    /// Minimum distance of automatically spaced grid lines in device units.
+    const MIN_GRID_DISTANCE: f64 = 50.0;
+
+    /// Maximum number of grid lines per axis, denser grids are skipped.
+    const MAX_GRID_LINES: f64 = 1000.0;
+
+    /**
+    Draws the overlay for `viewport` onto `cr`, whose current transformation
+    matrix is the one `viewport` is applied to.
+     */
+    fn draw(&self, viewport: &Viewport, cr: &cairo::Context) -> Result<(), cairo::Error> {
+        let visible = viewport.visible_world_bounds();
+        cr.new_path();
+        cr.set_operator(cairo::Operator::Over);
+        cr.set_line_width(self.line_width);
+        cr.set_line_cap(cairo::LineCap::Butt);
+        cr.set_dash(&[], 0.0);
+        cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Normal);
+        cr.set_font_size(self.font_size);
+
+        // Labels are drawn after all lines to keep them readable
+        let mut labels = Vec::new();
+
+        // Vertical grid lines, labeled at the lowest point in the image
+        let spacing = self.axis_grid_spacing(viewport.scale[0]);
+        for x in grid_values(visible.xmin(), visible.xmax(), spacing) {
+            let start = viewport.to_device([x, visible.ymin()]);
+            let end = viewport.to_device([x, visible.ymax()]);
+            self.draw_grid_line(cr, start, end)?;
+            let position = if start[1] > end[1] { start } else { end };
+            labels.push((position, format_coordinate(x, spacing)));
+        }
+
+        // Horizontal grid lines, labeled at the leftmost point in the image
+        let spacing = self.axis_grid_spacing(viewport.scale[1]);
+        for y in grid_values(visible.ymin(), visible.ymax(), spacing) {
+            let start = viewport.to_device([visible.xmin(), y]);
+            let end = viewport.to_device([visible.xmax(), y]);
+            self.draw_grid_line(cr, start, end)?;
+            let position = if start[0] < end[0] { start } else { end };
+            labels.push((position, format_coordinate(y, spacing)));
+        }
+
+        if let Some(bounding_box) = &self.bounding_box {
+            let corners = [
+                [bounding_box.xmin(), bounding_box.ymin()],
+                [bounding_box.xmax(), bounding_box.ymin()],
+                [bounding_box.xmax(), bounding_box.ymax()],
+                [bounding_box.xmin(), bounding_box.ymax()],
+            ];
+            for corner in corners {
+                let [x, y] = viewport.to_device(corner);
+                cr.line_to(x, y);
+            }
+            cr.close_path();
+            cr.set_source_rgb(1.0, 0.5, 0.0);
+            cr.stroke()?;
+        }
+
+        // Crosshair at the origin, with arms along the world axes
+        let arm = 2.0 * self.font_size;
+        let origin = viewport.to_device([0.0, 0.0]);
+        for (axis, color) in [([1.0, 0.0], [0.9, 0.0, 0.0]), ([0.0, 1.0], [0.0, 0.7, 0.0])] {
+            let tip = viewport.to_device(axis);
+            let (dx, dy) = (tip[0] - origin[0], tip[1] - origin[1]);
+            let length = dx.hypot(dy);
+            if length == 0.0 {
+                continue;
+            }
+            let (dx, dy) = (arm * dx / length, arm * dy / length);
+            cr.move_to(origin[0] - dx, origin[1] - dy);
+            cr.line_to(origin[0] + dx, origin[1] + dy);
+            cr.set_source_rgb(color[0], color[1], color[2]);
+            cr.set_line_width(2.0 * self.line_width);
+            cr.stroke()?;
+        }
+
+        for (position, text) in labels {
+            self.draw_label(viewport, cr, position, &text)?;
+        }
+        return Ok(());
+    }
+
+    /// Returns the grid spacing in world units for an axis with the given scale.
+    fn axis_grid_spacing(&self, scale: f64) -> f64 {
+        if let Some(spacing) = self.grid_spacing {
+            return spacing.abs();
+        }
+        let min_spacing = Self::MIN_GRID_DISTANCE / scale.abs();
+        let magnitude = 10f64.powf(min_spacing.log10().floor());
+        for factor in [1.0, 2.0, 5.0] {
+            if factor * magnitude >= min_spacing {
+                return factor * magnitude;
+            }
+        }
+        return 10.0 * magnitude;
+    }
+
+    fn draw_grid_line(
+        &self,
+        cr: &cairo::Context,
+        start: [f64; 2],
+        end: [f64; 2],
+    ) -> Result<(), cairo::Error> {
+        cr.move_to(start[0], start[1]);
+        cr.line_to(end[0], end[1]);
+        cr.set_source_rgba(0.5, 0.5, 0.5, 0.5);
+        return cr.stroke();
+    }
+
+    /**
+    Draws `text` next to the device point `position` on a bright background,
+    moved into the image if necessary.
+     */
+    fn draw_label(
+        &self,
+        viewport: &Viewport,
+        cr: &cairo::Context,
+        position: [f64; 2],
+        text: &str,
+    ) -> Result<(), cairo::Error> {
+        let extents = cr.text_extents(text)?;
+        let padding = 2.0;
+        let max_x = f64::from(viewport.width) - extents.width() - padding;
+        let max_y = f64::from(viewport.height) - padding;
+        let x = (position[0] + padding).min(max_x).max(padding);
+        let y = (position[1] - padding)
+            .min(max_y)
+            .max(self.font_size + padding);
+
+        cr.rectangle(
+            x + extents.x_bearing() - 1.0,
+            y + extents.y_bearing() - 1.0,
+            extents.width() + 2.0,
+            extents.height() + 2.0,
+        );
+        cr.set_source_rgba(1.0, 1.0, 1.0, 0.7);
+        cr.fill()?;
+
+        cr.move_to(x, y);
+        cr.set_source_rgb(0.2, 0.2, 0.2);
+        return cr.show_text(text);
+    }
+}
+
+/**
+Returns all multiples of `spacing` between `min` and `max`. Returns nothing if
+the spacing is not positive and finite or results in too many values.
+ */
+fn grid_values(min: f64, max: f64, spacing: f64) -> impl Iterator<Item = f64> {
+    let first = (min / spacing).ceil();
+    let last = (max / spacing).floor();
+    let valid = spacing > 0.0
+        && spacing.is_finite()
+        && first.is_finite()
+        && last.is_finite()
+        && last - first < DebugOverlay::MAX_GRID_LINES;
+    let (first, last) = if valid {
+        (first as i64, last as i64)
+    } else {
+        (1, 0)
+    };
+    return (first..=last).map(move |i| i as f64 * spacing);
+}
+
+/**
+Formats a grid coordinate with as many decimal places as needed to represent
+`spacing` (at most six).
+ */
+fn format_coordinate(value: f64, spacing: f64) -> String {
+    let mut decimals: usize = 0;
+    while decimals < 6 {
+        let scaled = spacing * 10f64.powi(decimals as i32);
+        if (scaled - scaled.round()).abs() < 1e-6 * scaled.max(1.0) {
+            break;
+        }
+        decimals += 1;
+    }
+    // Adding zero turns a negative zero into a positive one
+    return format!("{:.*}", decimals, value + 0.0);
+}
+
+/**
+Additional options for [`Viewport::write_to_file_with_options`] and
+[`Viewport::write_to_with_options`].
+
+Each option only affects the [`OutputFormat`]s listed in its documentation and
+is ignored by all other formats. The [`Default`] value results in the same
+output as [`Viewport::write_to_file`].
+ */
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WriteOptions {
+    /// Metadata of the document (.pdf only).
+    pub metadata: DocumentMetadata,
+    /// Options for .svg files.
+    pub svg: SvgOptions,
+    /// Restricts .ps and .eps files to the given PostScript language level. If
+    /// `None`, cairo uses the lowest level the drawing requires (at most 3).
+    pub ps_level: Option<cairo::PsLevel>,
+    /// Options for .png files.
+    pub png: PngOptions,
+    /// Options for the raster formats encoded via the image crate (e.g. .jpg).
+    #[cfg(feature = "image")]
+    pub raster: RasterOptions,
+}
+
+/**
+Options for the raster formats which are encoded via the
+[image](https://crates.io/crates/image) crate, see [`WriteOptions::raster`].
+
+Only available if the `image` feature is enabled.
+
+# Examples
+
+```
+use cairo_viewport::{OutputFormat, RasterOptions, Viewport, SideLength, WriteOptions};
+use bounding_box::BoundingBox;
+
+let bb = BoundingBox::new(-1.0, 1.0, -1.0, 1.0);
+let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(100));
+
+let options = WriteOptions {
+    raster: RasterOptions {
+        background: Some([1.0, 1.0, 1.0]),
+        jpeg_quality: Some(80),
+    },
+    ..Default::default()
+};
+let jpeg = viewport.write_to_with_options(Vec::new(), OutputFormat::Jpeg, &options, |cr| {
+    cr.arc(0.0, 0.0, 0.5, 0.0, 2.0 * std::f64::consts::PI);
+    return cr.fill();
+}).unwrap();
+assert!(jpeg.starts_with(&[0xFF, 0xD8]));
+```
+ */
+#[cfg(feature = "image")]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RasterOptions {
+    /**
+    Background color (red, green and blue in the range from 0 to 1) onto which
+    the drawing is composited before encoding, see [`PngOptions::background`].
+    If `None`, the transparency is kept, except for [`OutputFormat::Jpeg`]
+    which does not support transparency and uses a white background, since
+    drawings typically consist of dark strokes on a transparent background.
+     */
+    pub background: Option<[f64; 3]>,
+    /// Quality of [`OutputFormat::Jpeg`] images from 1 (worst) to 100 (best).
+    /// If `None`, the default of the image crate is used.
+    pub jpeg_quality: Option<u8>,
+}
+
+/**
+Options for .png files, see [`WriteOptions::png`].
+
+# Examples
+
+```
+use cairo_viewport::{OutputFormat, PngOptions, PngPixelFormat, Viewport, SideLength, WriteOptions};
+use bounding_box::BoundingBox;
+
+let bb = BoundingBox::new(-1.0, 1.0, -1.0, 1.0);
+let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(100));
+
+let options = WriteOptions {
+    png: PngOptions {
+        pixel_format: PngPixelFormat::Rgb,
+        background: Some([1.0, 1.0, 1.0]),
+        text: vec![("Software".into(), "cairo_viewport".into())],
+    },
+    ..Default::default()
+};
+let png = viewport.write_to_with_options(Vec::new(), OutputFormat::Png, &options, |cr| {
+    cr.arc(0.0, 0.0, 0.5, 0.0, 2.0 * std::f64::consts::PI);
+    return cr.fill();
+}).unwrap();
+assert!(png.starts_with(b"\x89PNG"));
+```
+ */
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PngOptions {
+    /// Pixel format of the encoded image.
+    pub pixel_format: PngPixelFormat,
+    /**
+    Background color (red, green and blue in the range from 0 to 1) onto which
+    the drawing is composited before encoding. This removes all transparency
+    from the image.
+
+    If `None`, [`PngPixelFormat::Rgba`] keeps the transparency, while
+    [`PngPixelFormat::Rgb`] and [`PngPixelFormat::Gray`] use a black
+    background. [`PngPixelFormat::Alpha`] ignores this value.
+     */
+    pub background: Option<[f64; 3]>,
+    /**
+    Text chunks as pairs of keyword and text, e.g.
+    `("Software".into(), "my_app 1.0".into())`.
+
+    The PNG specification predefines some keywords such as "Title", "Author",
+    "Description", "Software", "Source" and "Comment", but any other keyword
+    is allowed as well. Keywords need to consist of 1 to 79 printable ASCII
+    characters without leading, trailing or consecutive spaces, otherwise
+    [`Error::InvalidPngText`] is returned. Texts which only contain ASCII
+    characters are stored in `tEXt` chunks, all other texts in UTF-8 encoded
+    `iTXt` chunks.
+     */
+    pub text: Vec<(String, String)>,
+}
+
+impl PngOptions {
+    /**
+    Converts `surface` (created by [`Viewport::render_to_image_surface`]) into
+    the pixel format and background specified by `self`.
+     */
+    fn convert_surface(&self, surface: cairo::ImageSurface) -> Result<cairo::ImageSurface, Error> {
+        let format = match self.pixel_format {
+            PngPixelFormat::Rgba => cairo::Format::ARgb32,
+            PngPixelFormat::Rgb | PngPixelFormat::Gray => cairo::Format::Rgb24,
+            PngPixelFormat::Alpha => cairo::Format::A8,
+        };
+        if format == cairo::Format::ARgb32 && self.background.is_none() {
+            return Ok(surface);
+        }
+
+        let width = surface.width();
+        let height = surface.height();
+        let converted = cairo::ImageSurface::create(format, width, height)?;
+        let cr = cairo::Context::new(&converted)?;
+        if format != cairo::Format::A8 {
+            let [r, g, b] = self.background.unwrap_or([0.0, 0.0, 0.0]);
+            cr.set_source_rgb(r, g, b);
+            cr.paint()?;
+        }
+        cr.set_source_surface(&surface, 0.0, 0.0)?;
         cr.paint()?;
+        drop(cr);
+        converted.flush();
 
-        cr.move_to(-1.0, 0.0);
-        cr.line_to(1.0, 0.0);
-        cr.set_line_width(0.2);
-        cr.set_source_rgba(color[0], color[1], color[2], color[3]);
-        cr.stroke()?;
-
-        cr.move_to(0.0, -1.0);
-        cr.line_to(0.0, 1.0);
-        cr.set_line_width(0.2);
-        cr.set_source_rgba(color[0], color[1], color[2], color[3]);
-        cr.stroke()?;
+        if self.pixel_format != PngPixelFormat::Gray {
+            return Ok(converted);
+        }
 
-        return Ok(());
+        // cairo writes A8 surfaces as grayscale images, hence the luminance is
+        // stored in an A8 surface.
+        let mut gray = cairo::ImageSurface::create(cairo::Format::A8, width, height)?;
+        let gray_stride = gray.stride() as usize;
+        let stride = converted.stride() as usize;
+        {
+            let mut gray_data = gray.data().map_err(exclusive_borrow_error)?;
+            converted
+                .with_data(|data| {
+                    for (y, row) in data.chunks(stride).take(height as usize).enumerate() {
+                        for (x, pixel) in row.chunks_exact(4).take(width as usize).enumerate() {
+                            let rgb = u32::from_ne_bytes([pixel[0], pixel[1], pixel[2], pixel[3]]);
+                            let r = (rgb >> 16) & 0xff;
+                            let g = (rgb >> 8) & 0xff;
+                            let b = rgb & 0xff;
+                            // ITU-R BT.709 luma coefficients
+                            let luma = (2126 * r + 7152 * g + 722 * b + 5000) / 10000;
+                            gray_data[y * gray_stride + x] = luma as u8;
+                        }
+                    }
+                })
+                .map_err(exclusive_borrow_error)?;
+        }
+        return Ok(gray);
     }
 
-    let path = Path::new("tests/img/black_cross_compare_or_create_method.png");
+    /**
+    Inserts [`PngOptions::text`] as `tEXt` / `iTXt` chunks directly after the
+    header chunk of `png` (created by cairo).
+     */
+    fn insert_text_chunks(&self, png: Vec<u8>) -> Result<Vec<u8>, Error> {
+        // Signature (8 bytes) followed by the IHDR chunk (4 bytes length, 4 bytes
+        // type, 13 bytes data, 4 bytes CRC)
+        const HEADER_END: usize = 8 + 4 + 4 + 13 + 4;
+        debug_assert_eq!(&png[12..16], b"IHDR");
+
+        let mut output = Vec::with_capacity(png.len() + 64 * self.text.len());
+        output.extend_from_slice(&png[..HEADER_END]);
+        for (keyword, text) in self.text.iter() {
+            let valid_keyword = (1..=79).contains(&keyword.len())
+                && keyword.bytes().all(|byte| (b' '..=b'~').contains(&byte))
+                && !keyword.starts_with(' ')
+                && !keyword.ends_with(' ')
+                && !keyword.contains("  ");
+            if !valid_keyword || text.contains('\0') {
+                return Err(Error::InvalidPngText(keyword.clone()));
+            }
 
-    assert!(!path.exists());
+            let mut data = keyword.as_bytes().to_vec();
+            data.push(0);
+            let chunk_type = if text.is_ascii() {
+                b"tEXt"
+            } else {
+                // Uncompressed, no language tag and no translated keyword
+                data.extend_from_slice(&[0, 0, 0, 0]);
+                b"iTXt"
+            };
+            data.extend_from_slice(text.as_bytes());
+            write_png_chunk(&mut output, chunk_type, &data);
+        }
+        output.extend_from_slice(&png[HEADER_END..]);
+        return Ok(output);
+    }
+}
 
-    // Creates the reference image
-    viewport.compare_or_create(path, |cr| draw_cross(cr, [0.0, 0.0, 0.0, 1.0]), 0.99).expect("image is created");
+/**
+Pixel format of a .png file, see [`PngOptions::pixel_format`].
+ */
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum PngPixelFormat {
+    /// Color with transparency (cairo's [`ARgb32`](cairo::Format::ARgb32), default).
+    #[default]
+    Rgba,
+    /// Opaque color (cairo's [`Rgb24`](cairo::Format::Rgb24)).
+    Rgb,
+    /// Opaque grayscale image, derived from the luminance of the drawing.
+    Gray,
+    /**
+    Grayscale image which contains only the transparency of the drawing
+    (cairo's [`A8`](cairo::Format::A8)), e.g. for masks. Fully opaque pixels
+    are white, fully transparent pixels black.
+     */
+    Alpha,
+}
 
-    assert!(path.exists());
+/**
+Options for .svg files, see [`WriteOptions::svg`].
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SvgOptions {
+    /**
+    Unit of the `width` and `height` attributes of the SVG document. The
+    image units ([`Viewport::width`], [`Viewport::height`]) are interpreted in
+    this unit, e.g. a width of 100 with [`cairo::SvgUnit::Mm`] results in an
+    image which is 100 mm wide. If `None`, cairo's default (points) is used.
+     */
+    pub unit: Option<cairo::SvgUnit>,
+    /// Restricts the output to the given SVG version. If `None`, cairo's
+    /// default (SVG 1.1) is used.
+    pub version: Option<cairo::SvgVersion>,
+    /**
+    If `true`, the `viewBox` of the SVG document is given in drawing ("world")
+    coordinates instead of image coordinates.
+
+    The `viewBox` then equals [`Viewport::visible_world_bounds`] and the
+    content is wrapped in a group which transforms it back into image
+    coordinates. Elements which are added to the SVG document later (e.g. by
+    web tooling) can therefore be placed in world coordinates without knowing
+    the scale and origin of the [`Viewport`]. Since the y-axis of SVG always
+    points downwards, the y-coordinates of the `viewBox` are negated for
+    [`YAxis::Up`], i.e. the world point `(x, y)` is located at `(x, -y)`.
+    Rotated viewports are not supported and result in
+    [`Error::RotatedWorldViewBox`].
+     */
+    pub world_view_box: bool,
+}
 
-    // Reference image exists -> compare
-    viewport.compare_or_create(path, |cr| draw_cross(cr, [0.0, 0.0, 0.0, 1.0]), 0.99).expect("comparison succeeded");
+/**
+Metadata of a .pdf document, see [`WriteOptions::metadata`] and
+[`ViewportDocument::set_metadata`].
 
-    // Draw a blue cross
-    let err = viewport.compare_or_create(path, |cr| draw_cross(cr, [0.0, 0.0, 1.0, 1.0]), 0.99).unwrap_err();
-    match err {
-        cairo_viewport::Error::ImageCompFailed {
-            reference_image: _,
-            image_created_from_fn,
-        } => {
-            let _ = std::fs::remove_file(&image_created_from_fn).unwrap();
+Fields which are `None` are not written into the document. Text fields may
+contain any UTF-8 string (without null bytes).
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DocumentMetadata {
+    /// Title of the document.
+    pub title: Option<String>,
+    /// Author of the document.
+    pub author: Option<String>,
+    /// Subject of the document.
+    pub subject: Option<String>,
+    /// Comma-separated list of keywords.
+    pub keywords: Option<String>,
+    /// Name of the application which created the document.
+    pub creator: Option<String>,
+    /**
+    Creation date in the ISO 8601 format `YYYY-MM-DDThh:mm:ss`, optionally
+    followed by a time zone (`Z` for UTC or `+hh:mm` / `-hh:mm`). If `None`,
+    cairo uses the current time.
+     */
+    pub creation_date: Option<String>,
+}
+
+impl DocumentMetadata {
+    fn apply(&self, surface: &cairo::PdfSurface) -> Result<(), Error> {
+        let entries = [
+            (cairo::PdfMetadata::Title, &self.title),
+            (cairo::PdfMetadata::Author, &self.author),
+            (cairo::PdfMetadata::Subject, &self.subject),
+            (cairo::PdfMetadata::Keywords, &self.keywords),
+            (cairo::PdfMetadata::Creator, &self.creator),
+            (cairo::PdfMetadata::CreateDate, &self.creation_date),
+        ];
+        for (key, value) in entries {
+            if let Some(value) = value {
+                surface.set_metadata(key, value)?;
+            }
         }
-        _ => panic!("{err}"),
+        return Ok(());
     }
+}
 
-    // Remove the reference image
-    std::fs::remove_file(&path).unwrap();
-    ```
-    */
-    #[cfg(feature = "image-compare")]
-    pub fn compare_or_create<F, P>(
-        &self,
-        image: P,
-        draw_callback: F,
-        required_relative_similarity: f64,
-    ) -> Result<(), Error>
-    where
-        F: for<'a> FnOnce(&'a cairo::Context) -> Result<(), cairo::Error>,
-        P: AsRef<Path>,
-    {
-        return compare_or_create(
-            image,
-            |path: &Path| self.write_to_file(path, draw_callback),
-            required_relative_similarity,
-        );
+/**
+Target of a link created via [`Viewport::add_link`].
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkTarget {
+    /// An external URI, e.g. a website.
+    Uri(String),
+    /// The page with the given zero-based index in the same document.
+    Page(usize),
+}
+
+/**
+Appends a PNG chunk with the given type and data (including length and CRC) to
+`png`.
+ */
+fn write_png_chunk(png: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    png.extend_from_slice(chunk_type);
+    png.extend_from_slice(data);
+
+    // CRC-32 of chunk type and data as defined in the PNG specification
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in chunk_type.iter().chain(data) {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
     }
+    png.extend_from_slice(&(!crc).to_be_bytes());
+}
+
+/**
+Converts the pixels of an [`ARgb32`](cairo::Format::ARgb32) `surface` from
+premultiplied into straight alpha.
+ */
+#[cfg(feature = "image")]
+fn rgba_image_from_surface(surface: &cairo::ImageSurface) -> Result<image::RgbaImage, Error> {
+    let width = surface.width() as u32;
+    let height = surface.height() as u32;
+    let stride = surface.stride() as usize;
+    let mut image = image::RgbaImage::new(width, height);
+    surface
+        .with_data(|data| {
+            for (y, row) in data.chunks(stride).take(height as usize).enumerate() {
+                for (x, pixel) in row.chunks_exact(4).take(width as usize).enumerate() {
+                    let argb = u32::from_ne_bytes([pixel[0], pixel[1], pixel[2], pixel[3]]);
+                    let a = (argb >> 24) & 0xff;
+                    let unpremultiply = |c: u32| -> u8 {
+                        if a == 0 {
+                            return 0;
+                        }
+                        return ((c * 255 + a / 2) / a).min(255) as u8;
+                    };
+                    let r = unpremultiply((argb >> 16) & 0xff);
+                    let g = unpremultiply((argb >> 8) & 0xff);
+                    let b = unpremultiply(argb & 0xff);
+                    image.put_pixel(x as u32, y as u32, image::Rgba([r, g, b, a as u8]));
+                }
+            }
+        })
+        .map_err(exclusive_borrow_error)?;
+    return Ok(image);
+}
+
+/**
+Converts the error of borrowing the data of an [`ImageSurface`](cairo::ImageSurface)
+which is not shared with any other context.
+ */
+fn exclusive_borrow_error(error: cairo::BorrowError) -> Error {
+    return match error {
+        cairo::BorrowError::Cairo(error) => Error::CairoError(error),
+        cairo::BorrowError::NonExclusive => {
+            unreachable!("the surface is not shared with any other context")
+        }
+    };
+}
+
+/**
+Escapes a string value for the attributes of cairo's tag API, where strings are
+enclosed in single quotes.
+ */
+fn escape_tag_attribute(value: &str) -> String {
+    return value.replace('\\', "\\\\").replace('\'', "\\'");
 }
 
 /**
@@ -631,9 +3929,7 @@ where
     let p = reference_image.as_ref();
 
     // Try to get the file extension
-    let ext = try_get_file_ext_for_cairo(p)?;
-
-    if ext != "png" {
+    if OutputFormat::from_path(p)? != OutputFormat::Png {
         return Err(Error::UnknowFileExt(
             "when comparing images, only .png images are allowed.".into(),
         ));
@@ -649,7 +3945,7 @@ where
     tmp_image.push_str("_TEST_");
     tmp_image.push_str(&create_random_filename(30));
     let mut tmp_image = Path::new(&tmp_image).to_owned();
-    tmp_image.set_extension(ext);
+    tmp_image.set_extension(p.extension().expect("checked by OutputFormat::from_path"));
 
     // Create the temporary file.
     let _ = std::fs::File::create(&tmp_image)?;
@@ -763,6 +4059,185 @@ where
     }
 }
 
+/**
+Creates a temporary file in the directory of `path`, passes it to `write` and
+renames it to `path` afterwards. If `write` fails, the temporary file is removed.
+ */
+fn write_file_atomically<F>(path: &Path, write: F) -> Result<(), Error>
+where
+    F: FnOnce(std::fs::File) -> Result<std::fs::File, Error>,
+{
+    let (tmp_path, tmp_file) = create_temporary_file(path)?;
+    let result = write(tmp_file).and_then(|file| persist_temporary_file(&tmp_path, file, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp_path);
+    }
+    return result;
+}
+
+/**
+Creates a new, hidden temporary file next to `path` and returns its path
+together with the opened file.
+ */
+fn create_temporary_file(path: &Path) -> Result<(std::path::PathBuf, std::fs::File), Error> {
+    static COUNTER: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| Error::InvalidFilename(path.as_os_str().to_owned()))?;
+
+    // Find a file name which is not in use yet. The process ID and the counter
+    // make sure that parallel writes (e.g. in tests) do not interfere.
+    loop {
+        let mut tmp_name = std::ffi::OsString::from(".");
+        tmp_name.push(file_name);
+        tmp_name.push(format!(
+            ".{}-{}.tmp",
+            std::process::id(),
+            COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+        ));
+        let tmp_path = path.with_file_name(tmp_name);
+        match std::fs::File::create_new(&tmp_path) {
+            Ok(file) => return Ok((tmp_path, file)),
+            Err(error) if error.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(error) => return Err(error.into()),
+        }
+    }
+}
+
+/**
+Flushes `file` to disk and renames the temporary file at `tmp_path` to `path`.
+ */
+fn persist_temporary_file(tmp_path: &Path, file: std::fs::File, path: &Path) -> Result<(), Error> {
+    file.sync_all()?;
+    drop(file);
+    std::fs::rename(tmp_path, path)?;
+    return Ok(());
+}
+
+/**
+Returns the ink extents estimated by cairo for `recording` in its own
+coordinates, or [`Error::UnboundedDrawing`] if the painted area is unbounded.
+ */
+fn bounded_ink_extents(recording: &cairo::RecordingSurface) -> Result<BoundingBox, Error> {
+    // Coordinates are limited by cairo's fixed-point numbers with 24 integer bits
+    const LIMIT: f64 = (1 << 23) as f64 - 1.0;
+
+    let (x0, y0, width, height) = recording.ink_extents();
+    let bounded = [x0, y0, width, height]
+        .iter()
+        .all(|value| value.is_finite())
+        && width >= 0.0
+        && height >= 0.0
+        && x0 > -LIMIT
+        && y0 > -LIMIT
+        && x0 + width < LIMIT
+        && y0 + height < LIMIT;
+    if !bounded {
+        return Err(Error::UnboundedDrawing);
+    }
+    return Ok(BoundingBox::new(x0, x0 + width, y0, y0 + height));
+}
+
+/**
+Returns the ink extents of `recording` in its own coordinates. cairo's
+estimate is refined by rasterizing the recording, since e.g. curved strokes
+are padded by the maximum miter length.
+ */
+fn recording_ink_extents(recording: &cairo::RecordingSurface) -> Result<BoundingBox, Error> {
+    const MAX_PIXELS: f64 = (1 << 24) as f64;
+
+    let estimate = bounded_ink_extents(recording)?;
+    let [x0, y0] = [estimate.xmin(), estimate.ymin()];
+    let [width, height] = [estimate.width(), estimate.height()];
+    if width == 0.0 || height == 0.0 || width * height > MAX_PIXELS {
+        return Ok(estimate);
+    }
+
+    let mut surface = cairo::ImageSurface::create(cairo::Format::A8, width as i32, height as i32)?;
+    {
+        let cr = cairo::Context::new(&surface)?;
+        cr.set_source_surface(recording, -x0, -y0)?;
+        cr.paint()?;
+    }
+    surface.flush();
+
+    let stride = surface.stride() as usize;
+    let (columns, rows) = (width as usize, height as usize);
+    let data = surface.data().map_err(exclusive_borrow_error)?;
+    let mut painted: Option<[usize; 4]> = None;
+    for row in 0..rows {
+        for column in 0..columns {
+            if data[row * stride + column] == 0 {
+                continue;
+            }
+            painted = Some(match painted {
+                None => [column, column, row, row],
+                Some([xmin, xmax, ymin, ymax]) => [
+                    xmin.min(column),
+                    xmax.max(column),
+                    ymin.min(row),
+                    ymax.max(row),
+                ],
+            });
+        }
+    }
+
+    return match painted {
+        Some([xmin, xmax, ymin, ymax]) => Ok(BoundingBox::new(
+            x0 + xmin as f64,
+            x0 + (xmax + 1) as f64,
+            y0 + ymin as f64,
+            y0 + (ymax + 1) as f64,
+        )),
+        None => Ok(BoundingBox::new(x0, x0, y0, y0)),
+    };
+}
+
+/**
+Returns the bounding box of `bounding_box` rotated by `angle` around the origin.
+ */
+fn rotate_bounding_box(bounding_box: &BoundingBox, angle: f64) -> BoundingBox {
+    if angle == 0.0 {
+        return *bounding_box;
+    }
+
+    // Snap to exact values so that multiples of 90° do not introduce rounding
+    // errors into the image size.
+    let snap = |v: f64| {
+        for exact in [-1.0, 0.0, 1.0] {
+            if (v - exact).abs() < 1e-12 {
+                return exact;
+            }
+        }
+        return v;
+    };
+    let (sin, cos) = angle.sin_cos();
+    let (sin, cos) = (snap(sin), snap(cos));
+
+    return transform_bounding_box(bounding_box, |[x, y]| {
+        [cos * x - sin * y, sin * x + cos * y]
+    });
+}
+
+/**
+Returns the bounding box of the four corners of `bounding_box` transformed by
+`transform`.
+ */
+fn transform_bounding_box<F>(bounding_box: &BoundingBox, transform: F) -> BoundingBox
+where
+    F: Fn([f64; 2]) -> [f64; 2],
+{
+    let corners = [
+        [bounding_box.xmin(), bounding_box.ymin()],
+        [bounding_box.xmax(), bounding_box.ymin()],
+        [bounding_box.xmax(), bounding_box.ymax()],
+        [bounding_box.xmin(), bounding_box.ymax()],
+    ];
+    return BoundingBox::from_points(corners.into_iter().map(transform))
+        .expect("iterator is not empty");
+}
+
 #[cfg(feature = "image-compare")]
 fn create_random_filename(name_length: usize) -> String {
     const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ\
@@ -780,39 +4255,219 @@ fn create_random_filename(name_length: usize) -> String {
     return filename;
 }
 
-fn try_get_file_ext_for_cairo(path: &Path) -> Result<&str, Error> {
-    match path.extension().and_then(OsStr::to_str) {
-        Some(ext) => {
-            // Check if the provided file extension matches one of the available file
-            // extension types
-            if !CAIRO_FILE_EXTENSIONS.contains(&ext) {
-                let mut msg = format!(
-                    "The given file extension \"{}\" is not recognized. Available file extensions are: ",
-                    &ext
-                );
-                for (i, ext) in CAIRO_FILE_EXTENSIONS.iter().enumerate() {
-                    msg.push_str(ext);
-                    if (i + 1) < CAIRO_FILE_EXTENSIONS.len() {
-                        msg.push_str(", ");
-                    }
-                }
-                return Err(Error::UnknowFileExt(msg));
-            }
-            return Ok(ext);
-        }
-        None => {
-            let mut msg: String =
-                "No file extension has been recognized. Add one of the following file extensions: "
-                    .into();
-            for (i, ext) in CAIRO_FILE_EXTENSIONS.iter().enumerate() {
-                msg.push_str(ext);
-                if (i + 1) < CAIRO_FILE_EXTENSIONS.len() {
-                    msg.push_str(", ");
-                }
-            }
-            return Err(Error::UnknowFileExt(msg));
-        }
-    };
+/**
+Orientation of the y-axis of a [`Viewport`].
+
+cairo uses a coordinate system whose y-axis points downwards, i.e. the origin of
+the image is its top left corner. Many geometries (e.g. engineering drawings
+or plots) are however defined in a mathematical coordinate system whose y-axis
+points upwards. Using such a geometry with [`YAxis::Down`] results in drawings
+which are upside down.
+
+With [`YAxis::Up`], [`Viewport::apply`] flips the y-axis around the horizontal
+center line of the image, so that the largest y-value of a bounding box used
+in [`Viewport::from_bounding_box`] ends up at the top of the image. The font
+matrix of the context is mirrored as well, so that text is still drawn upright.
+Use [`Viewport::set_font_size`] instead of [`cairo::Context::set_font_size`]
+to change the font size without losing the mirroring.
+ */
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum YAxis {
+    /// The y-axis points downwards (cairo convention, default).
+    #[default]
+    Down,
+    /// The y-axis points upwards (mathematical convention).
+    Up,
+}
+
+/**
+Margin around a [`BoundingBox`], used in [`Viewport::from_bounding_box_with_margin`].
+
+The margin is given separately for each side of the bounding box. The sides are
+named after the extremal values of the bounding box they are adjacent to, e.g.
+[`Margin::xmin`] is the margin next to [`BoundingBox::xmin`]. Whether this is
+the left / right or top / bottom side of the image depends on the [`YAxis`]
+orientation of the [`Viewport`]. The unit of the margin values is defined by
+[`MarginUnit`].
+ */
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Margin {
+    /// Margin next to the minimum x-value of the bounding box.
+    pub xmin: f64,
+    /// Margin next to the maximum x-value of the bounding box.
+    pub xmax: f64,
+    /// Margin next to the minimum y-value of the bounding box.
+    pub ymin: f64,
+    /// Margin next to the maximum y-value of the bounding box.
+    pub ymax: f64,
+    /// Unit of the margin values.
+    pub unit: MarginUnit,
+}
+
+impl Margin {
+    /**
+    Creates a [`Margin`] from its components. The argument order matches that of
+    [`BoundingBox::new`].
+     */
+    pub fn new(xmin: f64, xmax: f64, ymin: f64, ymax: f64, unit: MarginUnit) -> Self {
+        return Margin {
+            xmin,
+            xmax,
+            ymin,
+            ymax,
+            unit,
+        };
+    }
+
+    /// Creates a margin of `value` world units on all sides.
+    pub fn world(value: f64) -> Self {
+        return Self::new(value, value, value, value, MarginUnit::World);
+    }
+
+    /// Creates a margin of `value` image units (e.g. pixels) on all sides.
+    pub fn device(value: f64) -> Self {
+        return Self::new(value, value, value, value, MarginUnit::Device);
+    }
+}
+
+/**
+Painting outside of the image area of a [`Viewport`], see
+[`Viewport::check_overflow`].
+
+The overflow is given separately for each side as a [`Margin`]: In device
+units, [`Margin::xmin`] and [`Margin::ymin`] are the overflow beyond the left
+and top image border, [`Margin::xmax`] and [`Margin::ymax`] that beyond the
+right and bottom border. In world units, the sides are named after the
+extremal values of [`Viewport::visible_world_bounds`]. Sides without overflow
+are zero.
+ */
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Overflow {
+    /// Overflow per side in world units.
+    pub world: Margin,
+    /// Overflow per side in device units (e.g. pixels).
+    pub device: Margin,
+    /// Bounding box of the painted area in world units.
+    pub ink_extents: BoundingBox,
+}
+
+impl std::fmt::Display for Overflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Margin {
+            xmin,
+            xmax,
+            ymin,
+            ymax,
+            ..
+        } = self.device;
+        write!(
+            f,
+            "left: {xmin}, right: {xmax}, top: {ymin}, bottom: {ymax} (device units); "
+        )?;
+        let Margin {
+            xmin,
+            xmax,
+            ymin,
+            ymax,
+            ..
+        } = self.world;
+        write!(
+            f,
+            "xmin: {xmin}, xmax: {xmax}, ymin: {ymin}, ymax: {ymax} (world units)"
+        )
+    }
+}
+
+/**
+Unit of the values of a [`Margin`].
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MarginUnit {
+    /// The margin is given in the coordinates of the drawing.
+    World,
+    /**
+    The margin is given in the units of the image, see [`SideLength`] for
+    their meaning for the different file types.
+     */
+    Device,
+}
+
+/**
+Scaling mode used in [`Viewport::fit_bounding_box`] to fit a [`BoundingBox`]
+into an image of fixed size.
+ */
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Fit {
+    /**
+    The bounding box is scaled uniformly so that it is fully visible. If the
+    aspect ratios of image and bounding box differ, there is unused space
+    ("letterboxing") along one dimension.
+     */
+    #[default]
+    Contain,
+    /**
+    The bounding box is scaled uniformly so that it covers the entire image. If
+    the aspect ratios of image and bounding box differ, the bounding box is
+    cropped along one dimension.
+     */
+    Cover,
+    /**
+    The bounding box is scaled non-uniformly so that it fills the image exactly.
+    If the aspect ratios of image and bounding box differ, the drawing is
+    distorted.
+     */
+    Stretch,
+}
+
+/**
+Placement of a [`BoundingBox`] within an image, see [`Viewport::fit_bounding_box`].
+
+The variants refer to the sides and corners of the image, e.g.
+[`Alignment::TopLeft`] places the upper left corner of the bounding box in the
+upper left corner of the image.
+ */
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Alignment {
+    /// Upper left corner.
+    TopLeft,
+    /// Center of the upper side.
+    Top,
+    /// Upper right corner.
+    TopRight,
+    /// Center of the left side.
+    Left,
+    /// Center of the image.
+    #[default]
+    Center,
+    /// Center of the right side.
+    Right,
+    /// Lower left corner.
+    BottomLeft,
+    /// Center of the lower side.
+    Bottom,
+    /// Lower right corner.
+    BottomRight,
+}
+
+impl Alignment {
+    /**
+    Returns the position of the alignment point relative to the image size,
+    with `[0.0, 0.0]` being the upper left and `[1.0, 1.0]` being the lower
+    right corner.
+     */
+    pub fn factors(&self) -> [f64; 2] {
+        return match self {
+            Alignment::TopLeft => [0.0, 0.0],
+            Alignment::Top => [0.5, 0.0],
+            Alignment::TopRight => [1.0, 0.0],
+            Alignment::Left => [0.0, 0.5],
+            Alignment::Center => [0.5, 0.5],
+            Alignment::Right => [1.0, 0.5],
+            Alignment::BottomLeft => [0.0, 1.0],
+            Alignment::Bottom => [0.5, 1.0],
+            Alignment::BottomRight => [1.0, 1.0],
+        };
+    }
 }
 
 /**
@@ -824,7 +4479,7 @@ the second side can then be derived from a [`BoundingBox`], see
 
 The exact meaning of "units" depends on the selected output format:
 - pdf: Points on the screen (1/72 inch on a screen at 100 % scale)
-- png: Pixel
+- png (and jpg, webp, tiff, bmp and gif): Pixel
 - ps: Points on the screen (1/72 inch on a screen at 100 % scale)
 - svg: CSS pixel on the screen (ca. 1/96 inch on a screen at 100 % scale)
  */
@@ -854,6 +4509,10 @@ impl From<SideLength> for u32 {
 impl SideLength {
     /**
     Calculates image width and height from `self` and a [`BoundingBox`].
+
+    If either the width or the height of the bounding box is zero, the side
+    length is applied to the other dimension and the degenerate dimension is
+    set to 1.
     ```
     use cairo_viewport::SideLength;
     use bounding_box::BoundingBox;
@@ -879,6 +4538,19 @@ impl SideLength {
      */
     pub fn to_width_and_height(&self, bounding_box: &BoundingBox) -> [u32; 2] {
         let ratio = bounding_box.width() / bounding_box.height();
+
+        // If one side of the bounding box is zero, the side length is applied to
+        // the other one.
+        if ratio.is_infinite() {
+            return [u32::from(*self).max(1), 1];
+        }
+        if ratio == 0.0 {
+            return [1, u32::from(*self).max(1)];
+        }
+        if ratio.is_nan() {
+            return [1, 1];
+        }
+
         let mut width: u32;
         let mut height: u32;
         match self {
@@ -947,8 +4619,38 @@ pub enum Error {
         /// The image created by the drawing function
         image_created_from_fn: std::path::PathBuf,
     },
+    /// The given matrix cannot be represented by a [`Viewport`], see
+    /// [`Viewport::try_from_matrix`].
+    UnsupportedMatrix(cairo::Matrix),
+    /// The given bounding box is not finite.
+    NonFiniteBoundingBox(BoundingBox),
+    /// Both width and height of the given bounding box are zero.
+    DegenerateBoundingBox(BoundingBox),
+    /// An iterator of bounded entities did not yield any item.
+    NoEntities,
+    /// The format does not support documents with multiple pages, see
+    /// [`ViewportDocument::create`].
+    UnsupportedDocumentFormat(OutputFormat),
+    /// The drawing paints an unbounded area, e.g. via [`cairo::Context::paint`]
+    /// without a clip, see [`RecordedDrawing::ink_extents`].
+    UnboundedDrawing,
+    /// The given margins leave no space for the bounding box within the image
+    /// side length, see [`Viewport::try_from_bounding_box_with_margin`].
+    MarginsExceedSideLength(Margin),
+    /// A world-space `viewBox` cannot be created for a viewport with the given
+    /// rotation, see [`SvgOptions::world_view_box`].
+    RotatedWorldViewBox(f64),
+    /// The SVG data written by cairo could not be parsed when setting a
+    /// world-space `viewBox`, see [`SvgOptions::world_view_box`].
+    MalformedSvg(String),
+    /// The text chunk with the given keyword cannot be written into a .png
+    /// file, see [`PngOptions::text`].
+    InvalidPngText(String),
+    /// The drawing paints outside of the image area, see
+    /// [`Viewport::check_overflow`].
+    DrawingOverflow(Overflow),
     /// An error occurred when trying to open that image
-    #[cfg(feature = "image-compare")]
+    #[cfg(feature = "image")]
     ImageError(image::ImageError),
     /// An error occurred when using the image-compare crate.
     #[cfg(feature = "image-compare")]
@@ -967,6 +4669,12 @@ impl From<std::io::Error> for Error {
     }
 }
 
+impl From<cairo::StreamWithError> for Error {
+    fn from(value: cairo::StreamWithError) -> Self {
+        Error::IoError(value.error)
+    }
+}
+
 impl From<cairo::IoError> for Error {
     fn from(value: cairo::IoError) -> Self {
         match value {
@@ -983,7 +4691,7 @@ impl From<image_compare::CompareError> for Error {
     }
 }
 
-#[cfg(feature = "image-compare")]
+#[cfg(feature = "image")]
 impl From<image::ImageError> for Error {
     fn from(value: image::ImageError) -> Self {
         Error::ImageError(value)
@@ -999,6 +4707,42 @@ impl std::fmt::Display for Error {
             Error::InvalidFilename(os_string) => {
                 write!(f, "invalid filename {}", os_string.to_string_lossy())
             }
+            Error::UnsupportedMatrix(matrix) => {
+                write!(f, "matrix {matrix:?} cannot be represented by a viewport")
+            }
+            Error::NonFiniteBoundingBox(bounding_box) => {
+                write!(f, "bounding box {bounding_box:?} is not finite")
+            }
+            Error::DegenerateBoundingBox(bounding_box) => {
+                write!(
+                    f,
+                    "width and height of bounding box {bounding_box:?} are zero"
+                )
+            }
+            Error::NoEntities => write!(f, "entities iterator must yield at least one item"),
+            Error::UnsupportedDocumentFormat(format) => {
+                write!(
+                    f,
+                    "format {format:?} does not support documents with multiple pages"
+                )
+            }
+            Error::UnboundedDrawing => write!(f, "the drawing paints an unbounded area"),
+            Error::MarginsExceedSideLength(margin) => {
+                write!(f, "margins {margin:?} exceed the image side length")
+            }
+            Error::InvalidPngText(keyword) => {
+                write!(f, "invalid keyword or text of PNG text chunk \"{keyword}\"")
+            }
+            Error::RotatedWorldViewBox(rotation) => {
+                write!(
+                    f,
+                    "world-space viewBox is not supported for viewports rotated by {rotation} rad"
+                )
+            }
+            Error::MalformedSvg(reason) => write!(f, "malformed SVG data: {reason}"),
+            Error::DrawingOverflow(overflow) => {
+                write!(f, "drawing exceeds the image area by {overflow}")
+            }
             #[cfg(feature = "image-compare")]
             Error::ImageCompFailed {
                 reference_image,
@@ -1013,10 +4757,42 @@ impl std::fmt::Display for Error {
             }
             #[cfg(feature = "image-compare")]
             Error::CompareError(error) => error.fmt(f),
-            #[cfg(feature = "image-compare")]
+            #[cfg(feature = "image")]
             Error::ImageError(error) => error.fmt(f),
         }
     }
 }
 
 impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_world_view_box_malformed_svg() {
+        let viewport = Viewport::new([0.0, 0.0], [1.0, 1.0], 10, 10);
+        let malformed_inputs: [&[u8]; 5] = [
+            b"\xff\xfe<svg></svg>",
+            b"<?xml version=\"1.0\"?>",
+            b"<svg width=\"10\"",
+            b"<svg width=\"10\">",
+            b"<svg viewBox=\"0 0 10 10>\n</svg>",
+        ];
+        for svg in malformed_inputs {
+            let result = viewport.set_world_view_box(svg.to_vec());
+            assert!(
+                matches!(result, Err(Error::MalformedSvg(_))),
+                "{}",
+                String::from_utf8_lossy(svg)
+            );
+        }
+
+        // Well-formed input is accepted
+        assert!(
+            viewport
+                .set_world_view_box(b"<svg viewBox=\"0 0 10 10\">\n</svg>".to_vec())
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,9 @@
+use std::path::PathBuf;
+
+/// Creates an empty directory for the files written by the test `name`.
+pub fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("cairo_viewport_{name}_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
@@ -0,0 +1,741 @@
+mod common;
+
+use bounding_box::BoundingBox;
+use cairo_viewport::{
+    DebugOverlay, Error, Margin, OutputFormat, PngOptions, PngPixelFormat, RecordedDrawing,
+    SideLength, SvgOptions, Viewport, WriteOptions, YAxis,
+};
+use common::test_dir;
+
+/// Returns the premultiplied pixel at `x`, `y` of an ARGB32 surface as `[a, r, g, b]`.
+fn argb_pixel(surface: &mut cairo::ImageSurface, x: usize, y: usize) -> [u8; 4] {
+    let stride = surface.stride() as usize;
+    let data = surface.data().unwrap();
+    let i = y * stride + 4 * x;
+    u32::from_ne_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]).to_be_bytes()
+}
+
+#[test]
+fn test_with_context_restores_matrix_on_error() {
+    let bb = BoundingBox::new(0.0, 2.0, 0.0, 1.0);
+    let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(200));
+
+    let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, 200, 100).unwrap();
+    let cr = cairo::Context::new(&surface).unwrap();
+    cr.translate(10.0, 20.0);
+    let matrix_before = cr.matrix();
+
+    let res = viewport.with_context(&cr, |cr| {
+        assert_eq!(cr.user_to_device(2.0, 1.0), (210.0, 120.0));
+        Err(cairo::Error::InvalidMatrix)
+    });
+    assert!(res.is_err());
+    assert_eq!(cr.matrix(), matrix_before);
+}
+
+#[test]
+fn test_y_axis_up_text_is_upright() {
+    let bb = BoundingBox::new(0.0, 10.0, 0.0, 5.0);
+    for y_axis in [YAxis::Down, YAxis::Up] {
+        let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(500)).with_y_axis(y_axis);
+
+        let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, 500, 250).unwrap();
+        let cr = cairo::Context::new(&surface).unwrap();
+        viewport
+            .with_context(&cr, |cr| {
+                viewport.set_font_size(cr, 2.0);
+                let extents = cr.text_extents("A")?;
+
+                // The glyph must extend upwards from the baseline on the device
+                let top =
+                    if extents.y_bearing().abs() > (extents.y_bearing() + extents.height()).abs() {
+                        extents.y_bearing()
+                    } else {
+                        extents.y_bearing() + extents.height()
+                    };
+                let (_, baseline) = cr.user_to_device(0.0, 0.0);
+                let (_, glyph_top) = cr.user_to_device(0.0, top);
+                assert!(glyph_top < baseline);
+                Ok(())
+            })
+            .unwrap();
+    }
+}
+
+#[test]
+fn test_non_uniform_scale_text_is_not_distorted() {
+    let bb = BoundingBox::new(0.0, 10.0, 0.0, 1.0);
+    for y_axis in [YAxis::Down, YAxis::Up] {
+        let viewport =
+            Viewport::from_bounding_box_with_scale(&bb, [10.0, 100.0]).with_y_axis(y_axis);
+
+        let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+        let cr = cairo::Context::new(&surface).unwrap();
+        viewport
+            .with_context(&cr, |cr| {
+                assert_eq!(
+                    cr.user_to_device_distance(1.0, 1.0)?,
+                    (10.0, 100.0 * y_axis_sign(y_axis))
+                );
+
+                viewport.set_font_size(cr, 2.0);
+                let m = cr.font_matrix();
+                let (dx, dy) = cr.user_to_device_distance(m.xx(), m.yy())?;
+                assert!((dx - 20.0).abs() < 1e-9);
+                assert!((dy - 20.0).abs() < 1e-9);
+                Ok(())
+            })
+            .unwrap();
+    }
+}
+
+fn y_axis_sign(y_axis: YAxis) -> f64 {
+    match y_axis {
+        YAxis::Down => 1.0,
+        YAxis::Up => -1.0,
+    }
+}
+
+#[test]
+fn test_to_device_and_to_world_match_context() {
+    let bb = BoundingBox::new(-3.0, 5.0, 1.0, 2.0);
+    for y_axis in [YAxis::Down, YAxis::Up] {
+        for rotation in [0.0, 0.3, -2.0] {
+            let mut viewport =
+                Viewport::from_bounding_box_with_rotation(&bb, SideLength::Long(400), rotation)
+                    .with_y_axis(y_axis);
+            viewport.scale[1] *= 1.5;
+
+            let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, 10, 10).unwrap();
+            let cr = cairo::Context::new(&surface).unwrap();
+            viewport.apply(&cr);
+
+            for point in [[0.0, 0.0], [-3.0, 1.0], [4.5, -7.25]] {
+                let (x, y) = cr.user_to_device(point[0], point[1]);
+                let device = viewport.to_device(point);
+                assert!((device[0] - x).abs() < 1e-9);
+                assert!((device[1] - y).abs() < 1e-9);
+
+                let world = viewport.to_world(device);
+                assert!((world[0] - point[0]).abs() < 1e-9);
+                assert!((world[1] - point[1]).abs() < 1e-9);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_matrix_round_trip() {
+    let bb = BoundingBox::new(-3.0, 5.0, 1.0, 2.0);
+    for y_axis in [YAxis::Down, YAxis::Up] {
+        for rotation in [0.0, 0.3, -2.0, std::f64::consts::PI] {
+            let mut viewport =
+                Viewport::from_bounding_box_with_rotation(&bb, SideLength::Long(400), rotation)
+                    .with_y_axis(y_axis);
+            viewport.scale[1] *= 1.5;
+
+            let converted =
+                Viewport::try_from_matrix(viewport.matrix(), viewport.width, viewport.height)
+                    .unwrap();
+            assert_eq!(converted.y_axis, viewport.y_axis);
+            assert_eq!(converted.width, viewport.width);
+            assert_eq!(converted.height, viewport.height);
+            for i in 0..2 {
+                assert!((converted.scale[i] - viewport.scale[i]).abs() < 1e-9);
+                assert!((converted.origin[i] - viewport.origin[i]).abs() < 1e-9);
+            }
+            let (sin, cos) = (converted.rotation - viewport.rotation).sin_cos();
+            assert!(sin.abs() < 1e-9 && cos > 0.0);
+        }
+    }
+}
+
+#[test]
+fn test_zoom_at_keeps_point_fixed() {
+    let bb = BoundingBox::new(-3.0, 5.0, 1.0, 2.0);
+    for y_axis in [YAxis::Down, YAxis::Up] {
+        let mut viewport =
+            Viewport::from_bounding_box_with_rotation(&bb, SideLength::Long(400), 0.7)
+                .with_y_axis(y_axis);
+        let cursor = [123.0, 45.0];
+        let world = viewport.to_world(cursor);
+        for factor in [1.1, 0.5, 3.0] {
+            viewport.zoom_at(cursor, factor);
+            let device = viewport.to_device(world);
+            assert!((device[0] - cursor[0]).abs() < 1e-9);
+            assert!((device[1] - cursor[1]).abs() < 1e-9);
+        }
+
+        let before = viewport.to_device([0.0, 0.0]);
+        viewport.pan_by_pixels(-5.0, 7.0);
+        let after = viewport.to_device([0.0, 0.0]);
+        assert!((after[0] - before[0] + 5.0).abs() < 1e-9);
+        assert!((after[1] - before[1] - 7.0).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn test_render_to_vec() {
+    let viewport =
+        Viewport::from_bounding_box(&BoundingBox::new(0.0, 3.0, 0.0, 1.0), SideLength::Long(30));
+    let draw_callback = |cr: &cairo::Context| {
+        cr.rectangle(0.5, 0.25, 1.0, 0.5);
+        cr.fill()
+    };
+
+    for (format, magic) in [
+        (OutputFormat::Pdf, b"%PDF".as_slice()),
+        (OutputFormat::Ps, b"%!PS".as_slice()),
+        (OutputFormat::Png, b"\x89PNG".as_slice()),
+    ] {
+        let bytes = viewport.render_to_vec(format, draw_callback).unwrap();
+        assert!(bytes.starts_with(magic), "{format:?}");
+    }
+
+    // The SVG root element follows the XML declaration
+    let svg = viewport
+        .render_to_vec(OutputFormat::Svg, draw_callback)
+        .unwrap();
+    let svg = String::from_utf8(svg).unwrap();
+    let (declaration, root) = svg.split_once("?>").unwrap();
+    assert!(declaration.starts_with("<?xml"));
+    assert!(root.trim_start().starts_with("<svg"));
+
+    // The .png file has the size of the viewport
+    let png = viewport
+        .render_to_vec(OutputFormat::Png, draw_callback)
+        .unwrap();
+    let surface = cairo::ImageSurface::create_from_png(&mut png.as_slice()).unwrap();
+    assert_eq!(surface.width() as u32, viewport.width);
+    assert_eq!(surface.height() as u32, viewport.height);
+    assert_eq!([viewport.width, viewport.height], [30, 10]);
+}
+
+#[test]
+fn test_write_to_failing_writer() {
+    // A writer which rejects all data, e.g. a closed network connection
+    struct FailingWriter;
+
+    impl std::io::Write for FailingWriter {
+        fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::BrokenPipe,
+                "connection closed",
+            ))
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let viewport =
+        Viewport::from_bounding_box(&BoundingBox::new(0.0, 1.0, 0.0, 1.0), SideLength::Long(10));
+    for format in [
+        OutputFormat::Png,
+        OutputFormat::Pdf,
+        OutputFormat::Ps,
+        OutputFormat::Eps,
+        OutputFormat::Svg,
+    ] {
+        let result = viewport.write_to(FailingWriter, format, |cr| cr.paint());
+        assert!(matches!(result, Err(Error::IoError(_))), "{format:?}");
+    }
+}
+
+#[test]
+fn test_write_to_file_replaces_existing_file() {
+    let dir = test_dir("atomic");
+    let path = dir.join("image.png");
+
+    // A large existing file must be replaced completely
+    std::fs::write(&path, vec![0xFFu8; 1_000_000]).unwrap();
+    let viewport =
+        Viewport::from_bounding_box(&BoundingBox::new(0.0, 1.0, 0.0, 1.0), SideLength::Long(10));
+    viewport.write_to_file(&path, |cr| cr.paint()).unwrap();
+    let written = std::fs::read(&path).unwrap();
+    assert_eq!(
+        written,
+        viewport
+            .render_to_vec(OutputFormat::Png, |cr| cr.paint())
+            .unwrap()
+    );
+
+    // A failing callback leaves the existing file untouched
+    let err = viewport.write_to_file(&path, |_| Err(cairo::Error::InvalidMatrix));
+    assert!(err.is_err());
+    assert_eq!(std::fs::read(&path).unwrap(), written);
+
+    // No temporary files are left behind
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_render_to_rgba_image_unpremultiplies() {
+    let viewport =
+        Viewport::from_bounding_box(&BoundingBox::new(0.0, 2.0, 0.0, 1.0), SideLength::Long(20));
+    let image = viewport
+        .render_to_rgba_image(|cr| {
+            // Left half: semi-transparent color, right half stays fully transparent
+            cr.set_source_rgba(0.2, 0.6, 1.0, 0.25);
+            cr.rectangle(0.0, 0.0, 1.0, 1.0);
+            cr.fill()
+        })
+        .unwrap();
+    assert_eq!(image.dimensions(), (20, 10));
+
+    let [r, g, b, a] = image.get_pixel(2, 5).0;
+    assert_eq!(a, 64);
+    // Rounding errors of the premultiplied 8-bit values are amplified by 255 / a
+    assert!((r as i32 - 51).abs() <= 4);
+    assert!((g as i32 - 153).abs() <= 4);
+    assert_eq!(b, 255);
+    assert_eq!(image.get_pixel(15, 5).0, [0, 0, 0, 0]);
+
+    // The surface itself still holds premultiplied values
+    let mut surface = viewport
+        .render_to_image_surface(|cr| {
+            cr.set_source_rgba(1.0, 1.0, 1.0, 0.5);
+            cr.paint()
+        })
+        .unwrap();
+    let data = surface.data().unwrap();
+    let argb = u32::from_ne_bytes([data[0], data[1], data[2], data[3]]);
+    assert_eq!(argb, 0x80808080);
+}
+
+#[test]
+fn test_svg_options() {
+    let bb = BoundingBox::new(0.0, 2.0, 0.0, 1.0);
+    let write = |viewport: Viewport, svg: SvgOptions| {
+        let options = WriteOptions {
+            svg,
+            ..Default::default()
+        };
+        viewport
+            .write_to_with_options(Vec::new(), OutputFormat::Svg, &options, |cr| {
+                cr.rectangle(0.5, 0.25, 1.0, 0.5);
+                cr.fill()
+            })
+            .map(|svg| String::from_utf8(svg).unwrap())
+    };
+
+    let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(200));
+    let svg = write(
+        viewport,
+        SvgOptions {
+            unit: Some(cairo::SvgUnit::Mm),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert!(svg.contains("width=\"200mm\""));
+    assert!(svg.contains("viewBox=\"0 0 200 100\""));
+
+    // World-space viewBox
+    let world = SvgOptions {
+        world_view_box: true,
+        ..Default::default()
+    };
+    let svg = write(viewport, world).unwrap();
+    assert_eq!(svg.matches("viewBox=").count(), 1);
+    assert!(svg.contains("viewBox=\"0 0 2 1\""));
+    assert!(svg.trim_end().ends_with("</g>\n</svg>"));
+
+    let svg = write(viewport.with_y_axis(YAxis::Up), world).unwrap();
+    assert!(svg.contains("viewBox=\"0 -1 2 1\""));
+
+    let rotated = Viewport::from_bounding_box_with_rotation(&bb, SideLength::Long(200), 0.5);
+    assert!(matches!(
+        write(rotated, world),
+        Err(Error::RotatedWorldViewBox(_))
+    ));
+}
+
+#[test]
+fn test_eps_bounding_box_and_ps_level() {
+    let bb = BoundingBox::new(0.0, 2.0, 0.0, 1.0);
+    let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(200));
+
+    // The drawing only covers a small part of the image
+    let draw_callback = |cr: &cairo::Context| {
+        cr.rectangle(0.5, 0.25, 0.5, 0.5);
+        cr.fill()
+    };
+    let eps = viewport
+        .render_to_vec(OutputFormat::Eps, draw_callback)
+        .unwrap();
+    let eps = String::from_utf8_lossy(&eps).into_owned();
+    assert!(eps.starts_with("%!PS-Adobe-3.0 EPSF-3.0"));
+    assert!(eps.contains("%%BoundingBox: 0 0 200 100\n"));
+
+    let options = WriteOptions {
+        ps_level: Some(cairo::PsLevel::_2),
+        ..Default::default()
+    };
+    for format in [OutputFormat::Ps, OutputFormat::Eps] {
+        let ps = viewport
+            .write_to_with_options(Vec::new(), format, &options, draw_callback)
+            .unwrap();
+        assert!(String::from_utf8_lossy(&ps).contains("%%LanguageLevel: 2"));
+    }
+}
+
+#[test]
+fn test_png_options() {
+    let viewport =
+        Viewport::from_bounding_box(&BoundingBox::new(0.0, 2.0, 0.0, 1.0), SideLength::Long(20));
+    let write = |png: PngOptions| {
+        let options = WriteOptions {
+            png,
+            ..Default::default()
+        };
+        viewport.write_to_with_options(Vec::new(), OutputFormat::Png, &options, |cr| {
+            // Left half: opaque red, right half stays fully transparent
+            cr.set_source_rgb(1.0, 0.0, 0.0);
+            cr.rectangle(0.0, 0.0, 1.0, 1.0);
+            cr.fill()
+        })
+    };
+
+    let png = write(PngOptions {
+        pixel_format: PngPixelFormat::Rgba,
+        background: Some([0.0, 0.0, 1.0]),
+        ..Default::default()
+    })
+    .unwrap();
+    let image = image::load_from_memory(&png).unwrap().into_rgba8();
+    assert_eq!(image.get_pixel(2, 5).0, [255, 0, 0, 255]);
+    assert_eq!(image.get_pixel(15, 5).0, [0, 0, 255, 255]);
+
+    let png = write(PngOptions {
+        pixel_format: PngPixelFormat::Rgb,
+        ..Default::default()
+    })
+    .unwrap();
+    let image = image::load_from_memory(&png).unwrap();
+    assert_eq!(image.color(), image::ColorType::Rgb8);
+    assert_eq!(image.into_rgb8().get_pixel(15, 5).0, [0, 0, 0]);
+
+    let png = write(PngOptions {
+        pixel_format: PngPixelFormat::Gray,
+        background: Some([1.0, 1.0, 1.0]),
+        ..Default::default()
+    })
+    .unwrap();
+    let image = image::load_from_memory(&png).unwrap();
+    assert_eq!(image.color(), image::ColorType::L8);
+    let image = image.into_luma8();
+    assert_eq!(image.get_pixel(2, 5).0, [54]);
+    assert_eq!(image.get_pixel(15, 5).0, [255]);
+
+    let png = write(PngOptions {
+        pixel_format: PngPixelFormat::Alpha,
+        ..Default::default()
+    })
+    .unwrap();
+    let image = image::load_from_memory(&png).unwrap().into_luma8();
+    assert_eq!(image.get_pixel(2, 5).0, [255]);
+    assert_eq!(image.get_pixel(15, 5).0, [0]);
+
+    // Text chunks are inserted with valid CRCs (checked by the decoder)
+    let png = write(PngOptions {
+        text: vec![
+            ("Software".into(), "cairo_viewport".into()),
+            ("Description".into(), "Grüße".into()),
+        ],
+        ..Default::default()
+    })
+    .unwrap();
+    assert!(image::load_from_memory(&png).is_ok());
+    let contains = |needle: &[u8]| png.windows(needle.len()).any(|w| w == needle);
+    assert!(contains(b"tEXtSoftware\0cairo_viewport"));
+    assert!(contains("iTXtDescription\0\0\0\0\0Grüße".as_bytes()));
+
+    assert!(matches!(
+        write(PngOptions {
+            text: vec![(" Software".into(), "cairo_viewport".into())],
+            ..Default::default()
+        }),
+        Err(Error::InvalidPngText(_))
+    ));
+}
+
+#[test]
+fn test_raster_formats_via_image_crate() {
+    assert_eq!(
+        OutputFormat::from_path("a.JPEG").unwrap(),
+        OutputFormat::Jpeg
+    );
+    assert_eq!(
+        OutputFormat::from_path("a.tif").unwrap(),
+        OutputFormat::Tiff
+    );
+    assert!(cairo_viewport::CAIRO_FILE_EXTENSIONS.contains(&"webp"));
+
+    let dir = test_dir("raster");
+    let viewport =
+        Viewport::from_bounding_box(&BoundingBox::new(0.0, 2.0, 0.0, 1.0), SideLength::Long(20));
+
+    for extension in ["jpg", "jpeg", "webp", "tiff", "bmp", "gif"] {
+        let path = dir.join(format!("image.{extension}"));
+        viewport
+            .write_to_file(&path, |cr| {
+                // Left half: opaque red, right half stays fully transparent
+                cr.set_source_rgb(1.0, 0.0, 0.0);
+                cr.rectangle(0.0, 0.0, 1.0, 1.0);
+                cr.fill()
+            })
+            .unwrap();
+
+        let image = image::open(&path).unwrap().into_rgba8();
+        assert_eq!(image.dimensions(), (20, 10), "{extension}");
+        let [r, g, b, a] = image.get_pixel(2, 5).0;
+        assert!(r > 240 && g < 15 && b < 15 && a == 255, "{extension}");
+
+        // JPEG has no transparency and uses a white background instead
+        let [r, g, b, a] = image.get_pixel(15, 5).0;
+        if extension.starts_with("jp") {
+            assert!(r > 240 && g > 240 && b > 240 && a == 255, "{extension}");
+        } else {
+            assert_eq!(a, 0, "{extension}");
+        }
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_fit_to_drawing() {
+    let calls = std::cell::Cell::new(0);
+    let (viewport, drawing) = Viewport::fit_to_drawing(
+        |cr| {
+            calls.set(calls.get() + 1);
+            cr.set_source_rgb(0.0, 0.0, 1.0);
+            cr.arc(3.0, -2.0, 1.0, 0.0, std::f64::consts::TAU);
+            cr.set_line_width(0.5);
+            cr.set_line_join(cairo::LineJoin::Round);
+            cr.stroke()
+        },
+        SideLength::Width(100),
+        Margin::device(0.0),
+    )
+    .unwrap();
+    assert_eq!(calls.get(), 1);
+
+    // The painted area is the circle plus half of the line width
+    let ink = drawing.ink_extents().unwrap();
+    let tol = 2.0 / RecordedDrawing::DEFAULT_RESOLUTION;
+    assert!((ink.xmin() - 1.75).abs() <= tol);
+    assert!((ink.xmax() - 4.25).abs() <= tol);
+    assert!((ink.ymin() + 3.25).abs() <= tol);
+    assert!((ink.ymax() + 0.75).abs() <= tol);
+    assert_eq!(viewport.width, 100);
+    assert!((viewport.height as i32 - 100).abs() <= 1);
+
+    // Replaying the recording draws the circle into the final output
+    let mut surface = viewport
+        .render_to_image_surface(|cr| drawing.replay(cr))
+        .unwrap();
+    assert_eq!(calls.get(), 1);
+    assert_eq!(argb_pixel(&mut surface, 2, 50), [255, 0, 0, 255]);
+    assert_eq!(argb_pixel(&mut surface, 50, 50), [0, 0, 0, 0]);
+
+    let err = Viewport::fit_to_drawing(|_| Ok(()), SideLength::Width(100), Margin::device(0.0));
+    assert!(matches!(err, Err(Error::DegenerateBoundingBox(_))));
+
+    // A background painted via cr.paint() has no finite extents
+    let err = Viewport::fit_to_drawing(
+        |cr| {
+            cr.set_source_rgb(1.0, 1.0, 1.0);
+            cr.paint()?;
+            cr.set_source_rgb(0.0, 0.0, 0.0);
+            cr.rectangle(0.0, 0.0, 1.0, 1.0);
+            cr.fill()
+        },
+        SideLength::Width(100),
+        Margin::device(0.0),
+    );
+    assert!(matches!(err, Err(Error::UnboundedDrawing)));
+    let drawing = RecordedDrawing::new(|cr| cr.paint()).unwrap();
+    assert!(matches!(
+        drawing.ink_extents(),
+        Err(Error::UnboundedDrawing)
+    ));
+}
+
+#[test]
+fn test_write_to_files() {
+    let dir = test_dir("write_to_files");
+    let viewport =
+        Viewport::from_bounding_box(&BoundingBox::new(0.0, 2.0, 0.0, 1.0), SideLength::Long(20));
+
+    let paths = [
+        dir.join("fig.png"),
+        dir.join("fig.svg"),
+        dir.join("fig.unknown"),
+        dir.join("fig.pdf"),
+    ];
+    let calls = std::cell::Cell::new(0);
+    let results = viewport
+        .write_to_files(&paths, |cr| {
+            calls.set(calls.get() + 1);
+            cr.set_source_rgb(1.0, 0.0, 0.0);
+            cr.rectangle(0.0, 0.0, 1.0, 1.0);
+            cr.fill()
+        })
+        .unwrap();
+    assert_eq!(calls.get(), 1);
+    assert_eq!(results.len(), 4);
+
+    // Only the file with the unknown extension failed
+    assert!(results[0].is_ok());
+    assert!(results[1].is_ok());
+    assert!(matches!(results[2], Err(Error::UnknowFileExt(_))));
+    assert!(results[3].is_ok());
+    assert!(!paths[2].exists());
+
+    let mut file = std::fs::File::open(&paths[0]).unwrap();
+    let mut surface = cairo::ImageSurface::create_from_png(&mut file).unwrap();
+    assert_eq!((surface.width(), surface.height()), (20, 10));
+    assert_eq!(argb_pixel(&mut surface, 2, 5), [255, 255, 0, 0]);
+    assert_eq!(argb_pixel(&mut surface, 15, 5), [0, 0, 0, 0]);
+
+    let svg = std::fs::read_to_string(&paths[1]).unwrap();
+    assert!(svg.contains("<svg"));
+    assert!(std::fs::read(&paths[3]).unwrap().starts_with(b"%PDF"));
+
+    // A failing callback writes no file at all
+    std::fs::remove_dir_all(&dir).unwrap();
+    std::fs::create_dir_all(&dir).unwrap();
+    let err = viewport.write_to_files(&paths, |_| Err(cairo::Error::InvalidMatrix));
+    assert!(err.is_err());
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_check_overflow() {
+    let bb = BoundingBox::new(0.0, 2.0, 0.0, 1.0);
+    for y_axis in [YAxis::Down, YAxis::Up] {
+        let viewport = Viewport::from_bounding_box(&bb, SideLength::Long(200)).with_y_axis(y_axis);
+
+        // A stroked circle fits into the image, even though cairo pads the
+        // estimated extents of curved strokes with the miter length
+        viewport
+            .check_overflow(|cr| {
+                cr.arc(1.0, 0.5, 0.4, 0.0, std::f64::consts::TAU);
+                cr.set_line_width(0.05);
+                cr.stroke()
+            })
+            .unwrap();
+
+        // Nothing painted at all
+        viewport.check_overflow(|_| Ok(())).unwrap();
+
+        // A background painted via cr.paint() cannot be checked, while a
+        // rectangle covering the visible area does not overflow
+        let result = viewport.check_overflow(|cr| {
+            cr.set_source_rgb(1.0, 1.0, 1.0);
+            cr.paint()
+        });
+        assert!(matches!(result, Err(Error::UnboundedDrawing)));
+        let visible = viewport.visible_world_bounds();
+        viewport
+            .check_overflow(|cr| {
+                cr.set_source_rgb(1.0, 1.0, 1.0);
+                cr.rectangle(
+                    visible.xmin(),
+                    visible.ymin(),
+                    visible.width(),
+                    visible.height(),
+                );
+                cr.fill()
+            })
+            .unwrap();
+
+        let result = viewport.check_overflow(|cr| {
+            cr.rectangle(0.0, 0.0, 2.5, 1.0);
+            cr.fill()
+        });
+        let Err(Error::DrawingOverflow(overflow)) = result else {
+            panic!("expected an overflow for {y_axis:?}");
+        };
+        assert_eq!(overflow.device.xmin, 0.0);
+        assert_eq!(overflow.device.xmax, 50.0);
+        assert_eq!(overflow.device.ymin, 0.0);
+        assert_eq!(overflow.device.ymax, 0.0);
+        assert_eq!(overflow.world.xmin, 0.0);
+        assert!((overflow.world.xmax - 0.5).abs() < 1e-9);
+        assert!(overflow.world.ymin.abs() < 1e-9);
+        assert!(overflow.world.ymax.abs() < 1e-9);
+        assert!((overflow.ink_extents.xmax() - 2.5).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn test_debug_overlay() {
+    let bb = BoundingBox::new(-1.0, 1.0, -1.0, 1.0);
+    let viewport =
+        Viewport::from_bounding_box(&bb, SideLength::Long(200)).with_debug_overlay(DebugOverlay {
+            bounding_box: Some(BoundingBox::new(-0.8, 0.8, -0.8, 0.8)),
+            ..Default::default()
+        });
+
+    let mut surface = viewport.render_to_image_surface(|_| Ok(())).unwrap();
+    let mut pixel = |x: usize, y: usize| argb_pixel(&mut surface, x, y);
+
+    // Origin crosshair in the center of the image: red x-axis, green y-axis
+    let [a, r, g, b] = pixel(110, 100);
+    assert!(a == 255 && r > 200 && g == 0 && b == 0);
+    let [a, r, g, b] = pixel(100, 110);
+    assert!(a == 255 && r == 0 && g > 150 && b == 0);
+
+    // Grid line at x = 0.5 (automatic spacing of 0.5 world units)
+    assert!(pixel(150, 30)[0] > 0);
+    assert_eq!(pixel(130, 30), [0, 0, 0, 0]);
+
+    // Outline of the bounding box (orange) at y = -0.8
+    let [a, r, g, b] = pixel(30, 20);
+    assert!(a > 0 && r > g && g > b);
+
+    // The overlay is not part of the checked drawing
+    viewport.check_overflow(|_| Ok(())).unwrap();
+
+    // Writing several files at once draws the overlay only once as well
+    let draw_callback = |cr: &cairo::Context| {
+        cr.rectangle(0.2, 0.2, 0.4, 0.4);
+        cr.fill()
+    };
+    let dir = test_dir("debug_overlay");
+    let path = dir.join("overlay.png");
+    let results = viewport
+        .write_to_files(std::slice::from_ref(&path), draw_callback)
+        .unwrap();
+    assert!(results[0].is_ok());
+    let png = viewport
+        .render_to_vec(OutputFormat::Png, draw_callback)
+        .unwrap();
+    let mut written =
+        cairo::ImageSurface::create_from_png(&mut std::fs::File::open(&path).unwrap()).unwrap();
+    let mut rendered = cairo::ImageSurface::create_from_png(&mut png.as_slice()).unwrap();
+    assert_eq!(written.data().unwrap()[..], rendered.data().unwrap()[..]);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    // The line width is given in device units and independent of the scale
+    // (counted across the grid line at y = 0.5, allowing for antialiasing)
+    let covered: Vec<usize> = [1, 10]
+        .into_iter()
+        .map(|factor| {
+            let zoomed = Viewport::from_bounding_box(&bb, SideLength::Long(200 * factor))
+                .with_debug_overlay(DebugOverlay::default());
+            let mut surface = zoomed.render_to_image_surface(|_| Ok(())).unwrap();
+            let (column, row) = ((factor * 1325 / 10) as usize, (factor * 150) as usize);
+            (row - 10..row + 10)
+                .filter(|&y| argb_pixel(&mut surface, column, y)[0] > 0)
+                .count()
+        })
+        .collect();
+    assert!((1..=3).contains(&covered[0]));
+    assert_eq!(covered[0], covered[1]);
+}
@@ -0,0 +1,184 @@
+mod common;
+
+use bounding_box::BoundingBox;
+use cairo_viewport::{
+    DocumentMetadata, Error, LinkTarget, OutputFormat, SideLength, Viewport, ViewportDocument,
+    WriteOptions,
+};
+use common::test_dir;
+
+#[test]
+fn test_pdf_document_with_multiple_page_sizes() {
+    let dir = test_dir("document_pdf");
+    let path = dir.join("report.pdf");
+
+    let small =
+        Viewport::from_bounding_box(&BoundingBox::new(0.0, 1.0, 0.0, 1.0), SideLength::Long(100));
+    let wide =
+        Viewport::from_bounding_box(&BoundingBox::new(0.0, 3.0, 0.0, 1.0), SideLength::Long(300));
+
+    let mut document = ViewportDocument::create(&path).unwrap();
+    document
+        .add_page(&small, |cr| {
+            cr.rectangle(0.25, 0.25, 0.5, 0.5);
+            cr.fill()
+        })
+        .unwrap();
+
+    // A failing page is not added to the document
+    assert!(
+        document
+            .add_page(&small, |_| Err(cairo::Error::InvalidMatrix))
+            .is_err()
+    );
+    assert_eq!(document.page_count(), 1);
+
+    document.add_page(&wide, |cr| cr.paint()).unwrap();
+    assert_eq!(document.page_count(), 2);
+
+    // Nothing is written before the document is finished
+    assert!(!path.exists());
+    document.finish().unwrap();
+
+    let pdf = String::from_utf8_lossy(&std::fs::read(&path).unwrap()).into_owned();
+    assert!(pdf.starts_with("%PDF"));
+    assert!(pdf.contains("/Count 2"));
+    assert!(pdf.contains("/MediaBox [ 0 0 100 100 ]"));
+    assert!(pdf.contains("/MediaBox [ 0 0 300 100 ]"));
+
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_ps_document() {
+    let dir = test_dir("document_ps");
+    let path = dir.join("report.ps");
+
+    let viewport =
+        Viewport::from_bounding_box(&BoundingBox::new(0.0, 2.0, 0.0, 1.0), SideLength::Long(200));
+    let mut document = ViewportDocument::create(&path).unwrap();
+    for _ in 0..3 {
+        document.add_page(&viewport, |cr| cr.paint()).unwrap();
+    }
+    document.finish().unwrap();
+
+    let ps = String::from_utf8_lossy(&std::fs::read(&path).unwrap()).into_owned();
+    assert!(ps.starts_with("%!PS"));
+    assert!(ps.contains("%%Pages: 3"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_document_unsupported_format_and_drop() {
+    let dir = test_dir("document_drop");
+
+    for (name, format) in [("a.png", OutputFormat::Png), ("a.svg", OutputFormat::Svg)] {
+        match ViewportDocument::create(dir.join(name)) {
+            Err(Error::UnsupportedDocumentFormat(f)) => assert_eq!(f, format),
+            _ => panic!("{name} must not be supported"),
+        }
+    }
+
+    // Dropping an unfinished document leaves no file behind
+    let mut document = ViewportDocument::create(dir.join("dropped.pdf")).unwrap();
+    let viewport =
+        Viewport::from_bounding_box(&BoundingBox::new(0.0, 1.0, 0.0, 1.0), SideLength::Long(10));
+    document.add_page(&viewport, |cr| cr.paint()).unwrap();
+    drop(document);
+
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_pdf_metadata_outline_and_links() {
+    let dir = test_dir("document_metadata");
+    let path = dir.join("report.pdf");
+
+    let metadata = DocumentMetadata {
+        title: Some("Quarterly report".into()),
+        author: Some("Jane Doe".into()),
+        keywords: Some("cairo, viewport".into()),
+        creation_date: Some("2024-01-31T12:00:00Z".into()),
+        ..Default::default()
+    };
+    let viewport =
+        Viewport::from_bounding_box(&BoundingBox::new(0.0, 2.0, 0.0, 1.0), SideLength::Long(200));
+
+    let mut document = ViewportDocument::create(&path).unwrap();
+    document.set_metadata(&metadata).unwrap();
+    let root = document
+        .add_outline(
+            cairo::PDF_OUTLINE_ROOT,
+            "Figures",
+            0,
+            cairo::PdfOutline::OPEN,
+        )
+        .unwrap();
+    for page in 0..2 {
+        document
+            .add_page(&viewport, |cr| {
+                cr.paint()?;
+                viewport.add_link(
+                    cr,
+                    &BoundingBox::new(0.0, 1.0, 0.0, 1.0),
+                    &LinkTarget::Page(0),
+                )
+            })
+            .unwrap();
+        document
+            .add_outline(
+                root,
+                &format!("Figure {page}"),
+                page,
+                cairo::PdfOutline::empty(),
+            )
+            .unwrap();
+    }
+    document.finish().unwrap();
+
+    let pdf = String::from_utf8_lossy(&std::fs::read(&path).unwrap()).into_owned();
+    assert!(pdf.contains("Quarterly report"));
+    assert!(pdf.contains("Jane Doe"));
+    assert!(pdf.contains("/Outlines"));
+
+    // Both pages contain a link covering the left half of the page which
+    // points to the first page
+    assert_eq!(pdf.matches("/Subtype /Link").count(), 2);
+    assert_eq!(pdf.matches("/Rect [ 0 0 100 100 ]").count(), 2);
+    let destinations: Vec<&str> = pdf
+        .match_indices("/Dest [")
+        .map(|(start, _)| {
+            let object = &pdf[start + "/Dest [".len()..];
+            &object[..object.find(" 0 R").unwrap()]
+        })
+        .collect();
+    assert_eq!(destinations.len(), 2);
+    assert_eq!(destinations[0], destinations[1]);
+    assert!(pdf.contains(&format!("{} 0 obj\n<< /Type /Page", destinations[0])));
+
+    // Single page files created by write_to_file use the same metadata
+    let single = dir.join("single.pdf");
+    let options = WriteOptions {
+        metadata,
+        ..Default::default()
+    };
+    viewport
+        .write_to_file_with_options(&single, &options, |cr| {
+            cr.paint()?;
+            viewport.add_link(
+                cr,
+                &BoundingBox::new(0.5, 1.5, 0.0, 1.0),
+                &LinkTarget::Uri("https://example.com/a'b".into()),
+            )
+        })
+        .unwrap();
+    let pdf = String::from_utf8_lossy(&std::fs::read(&single).unwrap()).into_owned();
+    assert!(pdf.contains("Quarterly report"));
+    assert_eq!(pdf.matches("/Subtype /Link").count(), 1);
+    assert!(pdf.contains("/Rect [ 50 0 150 100 ]"));
+    assert!(pdf.contains("/URI (https://example.com/a'b)"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -1,5 +1,5 @@
 use bounding_box::BoundingBox;
-use cairo_viewport::{SideLength, Viewport};
+use cairo_viewport::{Alignment, Error, Fit, Margin, MarginUnit, SideLength, Viewport, YAxis};
 
 #[test]
 #[should_panic]
@@ -17,6 +17,19 @@ fn test_from_bounded_iter_empty_vec() {
     assert!(Viewport::from_bounded_entities(elements.into_iter(), SideLength::Long(100)).is_err());
 }
 
+// An infinite common bounding box results in an error instead of a panic
+#[test]
+fn test_from_bounded_entities_infinite() {
+    let elements = vec![
+        BoundingBox::new(0.0, 1.0, 0.0, 1.0),
+        BoundingBox::new(0.0, f64::INFINITY, 0.0, 1.0),
+    ];
+    assert!(matches!(
+        Viewport::from_bounded_entities(elements.into_iter(), SideLength::Long(100)),
+        Err(Error::NonFiniteBoundingBox(_))
+    ));
+}
+
 #[test]
 fn test_from_bounding_box() {
     {
@@ -24,7 +37,7 @@ fn test_from_bounding_box() {
         let bb = BoundingBox::new(0.0, 1.0, 0.0, 1.0);
         let config = Viewport::from_bounding_box(&bb, SideLength::Long(max_height));
         assert_eq!(config.origin, [0.0, 0.0]);
-        assert_eq!(config.scale, max_height.into());
+        assert_eq!(config.scale, [max_height.into(), max_height.into()]);
         assert_eq!(config.height, max_height);
         assert_eq!(config.width, max_height);
     }
@@ -34,7 +47,7 @@ fn test_from_bounding_box() {
         bb.scale(2.0);
         let config = Viewport::from_bounding_box(&bb, SideLength::Long(max_height));
         assert_eq!(config.origin, [0.5, 0.5]);
-        assert_eq!(config.scale, 0.5 * max_height as f64);
+        assert_eq!(config.scale, [0.5 * max_height as f64; 2]);
         assert_eq!(config.height, max_height);
         assert_eq!(config.width, max_height);
     }
@@ -81,3 +94,266 @@ fn test_image_size_from_bounds() {
         assert_eq!(height, 500);
     }
 }
+
+#[test]
+fn test_from_bounding_box_with_device_margin() {
+    // Height is the long side of the bounding box
+    let bb = BoundingBox::new(1.0, 2.0, 1.0, 3.0);
+    let margin = Margin::new(10.0, 20.0, 30.0, 40.0, MarginUnit::Device);
+    let viewport = Viewport::from_bounding_box_with_margin(&bb, SideLength::Long(270), margin);
+    assert_eq!(viewport.height, 270);
+    assert_eq!(viewport.scale, [100.0, 100.0]);
+    assert_eq!(viewport.width, 130);
+
+    for y_axis in [YAxis::Down, YAxis::Up] {
+        let viewport = viewport.with_y_axis(y_axis);
+        let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, 130, 270).unwrap();
+        let cr = cairo::Context::new(&surface).unwrap();
+        viewport.apply(&cr);
+
+        let (x, y) = cr.user_to_device(1.0, 1.0);
+        assert!((x - 10.0).abs() < 1e-9);
+        match y_axis {
+            YAxis::Down => assert!((y - 30.0).abs() < 1e-9),
+            YAxis::Up => assert!((y - 240.0).abs() < 1e-9),
+        }
+        let (x, y) = cr.user_to_device(2.0, 3.0);
+        assert!((x - 110.0).abs() < 1e-9);
+        match y_axis {
+            YAxis::Down => assert!((y - 230.0).abs() < 1e-9),
+            YAxis::Up => assert!((y - 40.0).abs() < 1e-9),
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_from_bounding_box_with_too_large_margin() {
+    let bb = BoundingBox::new(0.0, 1.0, 0.0, 1.0);
+    let _ =
+        Viewport::from_bounding_box_with_margin(&bb, SideLength::Long(100), Margin::device(50.0));
+}
+
+#[test]
+fn test_try_from_bounding_box_with_device_margin() {
+    // The margins make the height the long side of the image
+    let bb = BoundingBox::new(0.0, 4.0, 0.0, 2.0);
+    let margin = Margin::new(0.0, 0.0, 200.0, 200.0, MarginUnit::Device);
+    let viewport =
+        Viewport::try_from_bounding_box_with_margin(&bb, SideLength::Long(500), margin).unwrap();
+    assert_eq!(viewport.width, 200);
+    assert_eq!(viewport.height, 500);
+    assert_eq!(viewport.scale, [50.0, 50.0]);
+    let viewport =
+        Viewport::try_from_bounding_box_with_margin(&bb, SideLength::Short(200), margin).unwrap();
+    assert_eq!(viewport.width, 200);
+    assert_eq!(viewport.height, 500);
+
+    // Horizontal line: The side length is applied to the width
+    let bb = BoundingBox::new(0.0, 4.0, 1.0, 1.0);
+    let viewport = Viewport::try_from_bounding_box_with_margin(
+        &bb,
+        SideLength::Height(100),
+        Margin::device(10.0),
+    )
+    .unwrap();
+    assert_eq!(viewport.scale, [20.0, 20.0]);
+    assert_eq!(viewport.width, 100);
+    assert_eq!(viewport.height, 20);
+    assert_eq!(viewport.to_device([0.0, 1.0]), [10.0, 10.0]);
+
+    assert!(matches!(
+        Viewport::try_from_bounding_box_with_margin(
+            &BoundingBox::new(1.0, 1.0, 1.0, 1.0),
+            SideLength::Long(100),
+            Margin::device(10.0)
+        ),
+        Err(Error::DegenerateBoundingBox(_))
+    ));
+    assert!(matches!(
+        Viewport::try_from_bounding_box_with_margin(
+            &bb,
+            SideLength::Long(100),
+            Margin::device(50.0)
+        ),
+        Err(Error::MarginsExceedSideLength(_))
+    ));
+}
+
+#[test]
+fn test_from_bounding_box_with_size_alignment() {
+    let bb = BoundingBox::new(0.0, 1.0, 0.0, 1.0);
+    let expected = [
+        (Alignment::TopLeft, [0.0, 0.0]),
+        (Alignment::Top, [50.0, 0.0]),
+        (Alignment::TopRight, [100.0, 0.0]),
+        (Alignment::Left, [0.0, 0.0]),
+        (Alignment::Center, [50.0, 0.0]),
+        (Alignment::Right, [100.0, 0.0]),
+        (Alignment::BottomLeft, [0.0, 0.0]),
+        (Alignment::Bottom, [50.0, 0.0]),
+        (Alignment::BottomRight, [100.0, 0.0]),
+    ];
+    for (alignment, corner) in expected {
+        let viewport =
+            Viewport::from_bounding_box_with_size(&bb, 200, 100, Fit::Contain, alignment);
+        assert_eq!(viewport.scale, [100.0, 100.0]);
+
+        let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, 200, 100).unwrap();
+        let cr = cairo::Context::new(&surface).unwrap();
+        viewport.apply(&cr);
+        let (x, y) = cr.user_to_device(0.0, 0.0);
+        assert_eq!([x, y], corner);
+    }
+
+    // Cover crops the bounding box vertically
+    let viewport =
+        Viewport::from_bounding_box_with_size(&bb, 200, 100, Fit::Cover, Alignment::Bottom);
+    assert_eq!(viewport.scale, [200.0, 200.0]);
+    assert_eq!(viewport.origin, [0.0, -0.5]);
+}
+
+#[test]
+fn test_from_bounding_box_with_rotation() {
+    let bb = BoundingBox::new(-1.0, 3.0, 2.0, 4.0);
+    for y_axis in [YAxis::Down, YAxis::Up] {
+        let viewport = Viewport::from_bounding_box_with_rotation(
+            &bb,
+            SideLength::Long(300),
+            std::f64::consts::FRAC_PI_4,
+        )
+        .with_y_axis(y_axis);
+
+        let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, 300, 300).unwrap();
+        let cr = cairo::Context::new(&surface).unwrap();
+        viewport.apply(&cr);
+
+        let corners = [
+            cr.user_to_device(bb.xmin(), bb.ymin()),
+            cr.user_to_device(bb.xmax(), bb.ymin()),
+            cr.user_to_device(bb.xmax(), bb.ymax()),
+            cr.user_to_device(bb.xmin(), bb.ymax()),
+        ];
+        let rotated = BoundingBox::from_points(corners.iter().map(|(x, y)| [*x, *y])).unwrap();
+        assert!(rotated.xmin().abs() < 1e-9);
+        assert!(rotated.ymin().abs() < 1e-9);
+        assert!((rotated.xmax() - viewport.width as f64).abs() < 1.0);
+        assert!((rotated.ymax() - viewport.height as f64).abs() < 1.0);
+    }
+}
+
+#[test]
+fn test_try_from_degenerate_bounding_box() {
+    // Vertical line
+    let bb = BoundingBox::new(2.0, 2.0, 0.0, 3.0);
+    for side_length in [
+        SideLength::Long(300),
+        SideLength::Short(300),
+        SideLength::Width(300),
+        SideLength::Height(300),
+    ] {
+        let [width, height] = side_length.to_width_and_height(&bb);
+        assert_eq!(width, 1);
+        assert_eq!(height, 300);
+
+        let viewport = Viewport::try_from_bounding_box(&bb, side_length).unwrap();
+        assert_eq!(viewport.scale, [100.0, 100.0]);
+        assert_eq!(viewport.to_device([2.0, 0.0]), [0.5, 0.0]);
+        assert_eq!(viewport.to_device([2.0, 3.0]), [0.5, 300.0]);
+    }
+
+    assert!(matches!(
+        Viewport::try_from_bounding_box(
+            &BoundingBox::new(0.0, 0.0, 0.0, 0.0),
+            SideLength::Long(100)
+        ),
+        Err(Error::DegenerateBoundingBox(_))
+    ));
+    assert!(matches!(
+        Viewport::try_from_bounded_entity(
+            BoundingBox::new(0.0, 1.0, f64::NEG_INFINITY, 0.0),
+            SideLength::Long(100)
+        ),
+        Err(Error::NonFiniteBoundingBox(_))
+    ));
+}
+
+#[test]
+fn test_try_constructors_with_invalid_bounding_box() {
+    let infinite = BoundingBox::new(0.0, f64::INFINITY, 0.0, 1.0);
+    let point = BoundingBox::new(1.0, 1.0, 2.0, 2.0);
+    let viewport = Viewport::new([0.0, 0.0], [1.0, 1.0], 100, 100);
+
+    assert!(matches!(
+        Viewport::try_from_bounding_box_with_rotation(&infinite, SideLength::Long(100), 0.5),
+        Err(Error::NonFiniteBoundingBox(_))
+    ));
+    assert!(matches!(
+        Viewport::try_from_bounding_box_with_rotation(&point, SideLength::Long(100), 0.5),
+        Err(Error::DegenerateBoundingBox(_))
+    ));
+    assert!(matches!(
+        Viewport::try_from_bounding_box_with_scale(&infinite, [1.0, 1.0]),
+        Err(Error::NonFiniteBoundingBox(_))
+    ));
+    assert!(matches!(
+        Viewport::try_from_bounding_box_with_scale(&point, [1.0, 1.0]),
+        Err(Error::DegenerateBoundingBox(_))
+    ));
+    assert!(matches!(
+        Viewport::try_from_bounding_box_with_margin(
+            &infinite,
+            SideLength::Long(100),
+            Margin::world(1.0)
+        ),
+        Err(Error::NonFiniteBoundingBox(_))
+    ));
+    assert!(matches!(
+        viewport.try_fit_bounding_box(&infinite, Fit::Contain, Alignment::Center),
+        Err(Error::NonFiniteBoundingBox(_))
+    ));
+    assert!(matches!(
+        Viewport::try_from_bounding_box_with_size(&point, 100, 100, Fit::Cover, Alignment::Center),
+        Err(Error::DegenerateBoundingBox(_))
+    ));
+
+    // A horizontal line is scaled by its width for all fit modes
+    let line = BoundingBox::new(0.0, 4.0, 1.0, 1.0);
+    for fit in [Fit::Contain, Fit::Cover, Fit::Stretch] {
+        let fitted = viewport
+            .try_fit_bounding_box(&line, fit, Alignment::Center)
+            .unwrap();
+        assert_eq!(fitted.scale, [25.0, 25.0]);
+        assert_eq!(fitted.to_device([0.0, 1.0]), [0.0, 50.0]);
+    }
+}
+
+#[test]
+fn test_try_zoom_to_degenerate_bounding_box() {
+    let mut viewport = Viewport::new([1.0, 2.0], [3.0, 3.0], 100, 100);
+
+    // Invalid bounding boxes leave the viewport unchanged
+    for bb in [
+        BoundingBox::new(1.0, 1.0, 2.0, 2.0),
+        BoundingBox::new(0.0, f64::INFINITY, 0.0, 1.0),
+    ] {
+        assert!(viewport.try_zoom_to_bounding_box(&bb).is_err());
+        assert_eq!(viewport.origin, [1.0, 2.0]);
+        assert_eq!(viewport.scale, [3.0, 3.0]);
+    }
+
+    // A vertical line is scaled by its height and centered horizontally
+    viewport
+        .try_zoom_to_bounding_box(&BoundingBox::new(2.0, 2.0, 0.0, 4.0))
+        .unwrap();
+    assert_eq!(viewport.scale, [25.0, 25.0]);
+    assert_eq!(viewport.to_device([2.0, 0.0]), [50.0, 0.0]);
+    assert_eq!(viewport.to_device([2.0, 4.0]), [50.0, 100.0]);
+}
+
+#[test]
+#[should_panic]
+fn test_zoom_to_point_bounding_box() {
+    let mut viewport = Viewport::new([0.0, 0.0], [1.0, 1.0], 100, 100);
+    viewport.zoom_to_bounding_box(&BoundingBox::new(1.0, 1.0, 2.0, 2.0));
+}